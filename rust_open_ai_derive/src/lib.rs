@@ -0,0 +1,46 @@
+//! Derive macros defining function tools for `rust_open_ai`, re-exported from
+//! `rust_open_ai::schema` with the `derive` feature.
+//!
+//! The generated code refers to the main crate as `::rust_open_ai`.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+mod attrs;
+mod schema;
+mod tool;
+
+/// Implements `ToolSchema` for a struct with named fields, as an object, or for an enum whose
+/// variants have no fields, as a string enumeration. Doc comments become descriptions and serde
+/// renames are followed.
+#[proc_macro_derive(ToolSchema, attributes(serde))]
+pub fn derive_tool_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema::derive_tool_schema(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `ToolArgs` and `ToolSchema` for the arguments of a tool, named after the struct in
+/// snake case unless renamed with `#[serde(rename = "...")]`.
+#[proc_macro_derive(ToolArgs, attributes(serde))]
+pub fn derive_tool_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema::derive_tool_args(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Defines a tool from a function, generating a `<Function>Args` struct implementing `ToolArgs`
+/// whose `call` invokes the function. The tool is named after the function unless set with
+/// `#[tool(name = "...")]`.
+#[proc_macro_attribute]
+pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut attrs = tool::ToolAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse(meta));
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+    tool::expand(attrs, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
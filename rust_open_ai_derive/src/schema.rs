@@ -0,0 +1,130 @@
+//! `#[derive(ToolSchema)]` and `#[derive(ToolArgs)]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DataEnum, DeriveInput, Fields, FieldsNamed};
+
+use crate::attrs::{doc_comment, validate_name, RenameRule, SerdeAttrs};
+
+pub fn derive_tool_schema(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let container = SerdeAttrs::parse(&input.attrs)?;
+    let description = doc_comment(&input.attrs).map(|doc| quote!(.with_description(#doc)));
+    let schema = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => object_schema(fields, &container)?,
+            _ => return Err(named_fields_error(input)),
+        },
+        Data::Enum(data) => enumeration_schema(data, &container)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "ToolSchema cannot be derived for unions",
+            ))
+        }
+    };
+    impl_tool_schema(input, quote!(#schema #description))
+}
+
+/// Implements `ToolArgs` for a struct, along with `ToolSchema`. The struct's doc comment is the
+/// description of the tool, and its serde name (`#[serde(rename = "...")]`) the name of the
+/// tool, which otherwise is the struct name in snake case.
+pub fn derive_tool_args(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields,
+            _ => return Err(named_fields_error(input)),
+        },
+        _ => return Err(named_fields_error(input)),
+    };
+    let container = SerdeAttrs::parse(&input.attrs)?;
+    let name = match &container.rename {
+        Some(rename) => rename.clone(),
+        None => RenameRule::Snake.apply_to_variant(&input.ident.unraw().to_string()),
+    };
+    validate_name(&name, input.ident.span())?;
+    let description = doc_comment(&input.attrs).unwrap_or_default();
+    let schema = impl_tool_schema(input, object_schema(fields, &container)?)?;
+
+    let ident = &input.ident;
+    Ok(quote! {
+        #schema
+
+        impl ::rust_open_ai::schema::ToolArgs for #ident {
+            const NAME: &'static str = #name;
+            const DESCRIPTION: &'static str = #description;
+        }
+    })
+}
+
+fn impl_tool_schema(input: &DeriveInput, schema: TokenStream) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "tool schemas cannot be derived for generic types",
+        ));
+    }
+    let ident = &input.ident;
+    Ok(quote! {
+        impl ::rust_open_ai::schema::ToolSchema for #ident {
+            fn tool_schema() -> ::rust_open_ai::schema::JsonSchema {
+                #schema
+            }
+        }
+    })
+}
+
+/// Every field is required, as `strict` mode demands, so `Option` fields are nullable instead.
+fn object_schema(fields: &FieldsNamed, container: &SerdeAttrs) -> syn::Result<TokenStream> {
+    let mut properties = Vec::new();
+    for field in &fields.named {
+        let serde = SerdeAttrs::parse(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let name = match (serde.rename, container.rename_all) {
+            (Some(rename), _) => rename,
+            (None, Some(rule)) => rule.apply_to_field(&ident.unraw().to_string()),
+            (None, None) => ident.unraw().to_string(),
+        };
+        let ty = &field.ty;
+        let description = doc_comment(&field.attrs).map(|doc| quote!(.with_description(#doc)));
+        properties.push(quote! {
+            .with_property(
+                #name,
+                <#ty as ::rust_open_ai::schema::ToolSchema>::tool_schema() #description,
+            )
+        });
+    }
+    Ok(quote!(::rust_open_ai::schema::JsonSchema::object() #(#properties)*))
+}
+
+fn enumeration_schema(data: &DataEnum, container: &SerdeAttrs) -> syn::Result<TokenStream> {
+    let mut values = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "ToolSchema can only be derived for enums whose variants have no fields",
+            ));
+        }
+        let serde = SerdeAttrs::parse(&variant.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        values.push(match (serde.rename, container.rename_all) {
+            (Some(rename), _) => rename,
+            (None, Some(rule)) => rule.apply_to_variant(&variant.ident.unraw().to_string()),
+            (None, None) => variant.ident.unraw().to_string(),
+        });
+    }
+    Ok(quote!(::rust_open_ai::schema::JsonSchema::enumeration([#(#values),*])))
+}
+
+fn named_fields_error(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "tool arguments must be a struct with named fields",
+    )
+}
@@ -0,0 +1,119 @@
+//! `#[tool]`, turning a function into a tool through a generated `ToolArgs` struct.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{FnArg, ItemFn, LitStr, Pat, Type};
+
+use crate::attrs::{validate_name, RenameRule};
+
+/// The arguments of `#[tool(...)]`.
+#[derive(Default)]
+pub struct ToolAttrs {
+    name: Option<LitStr>,
+}
+
+impl ToolAttrs {
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported tool attribute, expected `name`"))
+        }
+    }
+}
+
+/// Generates `<Function>Args`, holding one field per parameter and calling the function from
+/// `call`. Doc comments on the function and its parameters become the descriptions of the tool
+/// and its arguments, and are moved from the parameters to the fields, as rustc rejects them on
+/// parameters.
+pub fn expand(attrs: ToolAttrs, mut function: ItemFn) -> syn::Result<TokenStream> {
+    let sig = &mut function.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "tools cannot be generic functions",
+        ));
+    }
+    let fn_ident = sig.ident.clone();
+    let (name, span) = match &attrs.name {
+        Some(name) => (name.value(), name.span()),
+        None => (fn_ident.unraw().to_string(), fn_ident.span()),
+    };
+    validate_name(&name, span)?;
+    let args_ident = format_ident!(
+        "{}Args",
+        RenameRule::Pascal.apply_to_field(&fn_ident.unraw().to_string()),
+        span = fn_ident.span()
+    );
+
+    let mut fields = Vec::new();
+    let mut arguments = Vec::new();
+    for input in sig.inputs.iter_mut() {
+        let input = match input {
+            FnArg::Typed(input) => input,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "tools cannot take self, use a free function",
+                ))
+            }
+        };
+        let ident = match &*input.pat {
+            Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => pat.ident.clone(),
+            pat => {
+                return Err(syn::Error::new_spanned(
+                    pat,
+                    "tool parameters must be plain identifiers",
+                ))
+            }
+        };
+        if let Type::Reference(ty) = &*input.ty {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "tool parameters must be owned, as they are decoded from the arguments",
+            ));
+        }
+        let (docs, attrs) = input
+            .attrs
+            .drain(..)
+            .partition::<Vec<_>, _>(|attr| attr.path().is_ident("doc"));
+        input.attrs = attrs;
+        let ty = &input.ty;
+        fields.push(quote!(#(#docs)* pub #ident: #ty));
+        arguments.push(ident);
+    }
+
+    let vis = &function.vis;
+    let docs = function
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"));
+    let asyncness = &function.sig.asyncness;
+    let output = &function.sig.output;
+    let call = match asyncness {
+        Some(_) => quote!(#fn_ident(#(self.#arguments),*).await),
+        None => quote!(#fn_ident(#(self.#arguments),*)),
+    };
+    let call_doc = format!("Calls [`{}`] with the decoded arguments.", fn_ident);
+
+    Ok(quote! {
+        #function
+
+        #(#docs)*
+        #[derive(::rust_open_ai::__private::serde::Deserialize, ::rust_open_ai::schema::ToolArgs)]
+        #[serde(crate = "::rust_open_ai::__private::serde", rename = #name)]
+        #vis struct #args_ident {
+            #(#fields,)*
+        }
+
+        impl #args_ident {
+            #[doc = #call_doc]
+            #vis #asyncness fn call(self) #output {
+                #call
+            }
+        }
+    })
+}
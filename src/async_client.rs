@@ -0,0 +1,517 @@
+use crate::moderation::ModerationType;
+use std::collections::HashMap;
+
+use crate::client_builder::OpenAIClientBuilder;
+use crate::credentials;
+use crate::networking::transport::AsyncTransport;
+use crate::networking::{
+    AssistantsVersion, AsyncAssistantActions, AsyncFileActions, AsyncMessageActions,
+    AsyncModelActions, AsyncNetworking, AsyncPaginator, AsyncRunActions, AsyncThreadActions,
+    AzureConfig, KeyPool, NetworkingConfig, RateLimiter, ResponseMetadata, RetryPolicy,
+};
+use crate::strip_edges;
+use crate::types::assistant::{Assistant, AssistantFile};
+use crate::types::common::{ApiList, DeletionStatus, Identifiable, ListParams};
+use crate::types::error::OpenApiError;
+use crate::types::file::File;
+use crate::types::message::{Message, MessageFile};
+use crate::types::model::Model;
+use crate::types::moderation::Moderation;
+use crate::types::run::{Run, RunStep};
+use crate::types::thread::Thread;
+
+// doc sections: arguments, examples, panics, safety, errors
+
+/// # Async client for interacting with OpenAI's API.
+///
+/// Mirrors `OpenAIClient`, with every fallible method returning a future.
+///
+/// ## Arguments
+/// - `networking`: An `AsyncNetworking` struct that handles all networking with the API using the async reqwest client
+///
+/// ## Examples
+/// ```no_run
+/// # use rust_open_ai::AsyncOpenAIClient;
+/// # async fn example() -> Result<(), rust_open_ai::error::OpenApiError> {
+/// let client = AsyncOpenAIClient::new("sk-...", None)?;
+/// let models = client.list_models().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+/// May return an `OpenApiError` error type on fallible methods. This can be determined by methods
+/// with a return type of `Result`.
+///
+pub struct AsyncOpenAIClient {
+    networking: AsyncNetworking,
+}
+
+impl AsyncOpenAIClient {
+    /// Attempts to pull OPEN_API_KEY from ENV
+    pub fn obtain_key() -> Result<String, OpenApiError> {
+        credentials::obtain_key()
+    }
+
+    /// Prompts user for an api key on stdin. The environment is left untouched, as mutating it is
+    /// unsafe in multithreaded programs.
+    pub fn prompt_key() -> Result<String, OpenApiError> {
+        credentials::prompt_key()
+    }
+
+    /// Generate a new AsyncOpenAIClient supplying both the Apikey and Organization ID
+    ///
+    /// The base url is read from `OPENAI_BASE_URL` when set, otherwise the OpenAI api is used.
+    pub fn new<A: Into<String>>(
+        apikey: A,
+        organization_id: Option<String>,
+    ) -> Result<Self, OpenApiError> {
+        let apikey = apikey.into();
+        let base_url = NetworkingConfig::base_url_from_env()?;
+        Ok(Self {
+            networking: AsyncNetworking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
+        })
+    }
+
+    /// Generate a new AsyncOpenAIClient targeting an OpenAI compatible server at `base_url`, e.g.
+    /// `http://localhost:8000/v1`. Endpoints are resolved relative to the base path.
+    pub fn new_with_base_url<A: Into<String>, U: AsRef<str>>(
+        apikey: A,
+        organization_id: Option<String>,
+        base_url: U,
+    ) -> Result<Self, OpenApiError> {
+        let apikey = apikey.into();
+        let base_url = NetworkingConfig::parse_base_url(base_url)?;
+        Ok(Self {
+            networking: AsyncNetworking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
+        })
+    }
+
+    /// Generate a new AsyncOpenAIClient for Azure OpenAI, where `endpoint` is the resource endpoint
+    /// (e.g. `https://{resource}.openai.azure.com/`) and `azure` holds the api version and the
+    /// model to deployment table.
+    pub fn new_azure<A: Into<String>, U: AsRef<str>>(
+        apikey: A,
+        endpoint: U,
+        azure: AzureConfig,
+    ) -> Result<Self, OpenApiError> {
+        let apikey = apikey.into();
+        let base_url = NetworkingConfig::parse_base_url(endpoint)?;
+        Ok(Self {
+            networking: AsyncNetworking::new(
+                NetworkingConfig::new(apikey, None, base_url)?.with_azure(azure),
+            ),
+        })
+    }
+
+    /// Wraps a `AsyncNetworking` configured by `OpenAIClientBuilder`.
+    pub(crate) fn from_networking(networking: AsyncNetworking) -> Self {
+        Self { networking }
+    }
+
+    /// Generate a new AsyncOpenAIClient from the profile `name` of the config file, by default
+    /// `~/.config/rust_open_ai/config.toml`. Environment variables override the profile.
+    pub fn from_profile(name: &str) -> Result<Self, OpenApiError> {
+        OpenAIClientBuilder::from_profile(name)?.build_async()
+    }
+
+    /// Generate a new AsyncOpenAIClient distributing requests over the keys of `key_pool`, see `KeyPool`.
+    ///
+    /// The base url is read from `OPENAI_BASE_URL` when set, otherwise the OpenAI api is used.
+    pub fn new_with_key_pool(key_pool: KeyPool) -> Result<Self, OpenApiError> {
+        if key_pool.is_empty() {
+            return Err(OpenApiError::ClientError(String::from(
+                "Key pool must contain at least one key",
+            )));
+        }
+        let base_url = NetworkingConfig::base_url_from_env()?;
+        let config = NetworkingConfig::new("", None, base_url)?.with_key_pool(key_pool);
+        Ok(Self::from_networking(AsyncNetworking::new(config)))
+    }
+
+    /// Generate a new AsyncOpenAIClient and if apikey is not found return an error
+    pub fn new_with_env(organization_id: Option<String>) -> Result<Self, OpenApiError> {
+        let apikey = AsyncOpenAIClient::obtain_key()?;
+        let base_url = NetworkingConfig::base_url_from_env()?;
+        Ok(Self {
+            networking: AsyncNetworking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
+        })
+    }
+
+    /// Generate a new AsyncOpenAIClient and if apikey is not found, prompt user for key.
+    pub fn new_with_prompt(organization_id: Option<String>) -> Result<Self, OpenApiError> {
+        let mut apikey = AsyncOpenAIClient::obtain_key();
+        if apikey.is_err() {
+            apikey = AsyncOpenAIClient::prompt_key()
+        }
+        let apikey = apikey?;
+        let base_url = NetworkingConfig::base_url_from_env()?;
+        Ok(Self {
+            networking: AsyncNetworking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
+        })
+    }
+
+    /// Sets the Assistants api version sent as `OpenAI-Beta`, by default `AssistantsVersion::V2`.
+    pub fn with_assistants_version(self, assistants_version: AssistantsVersion) -> Self {
+        Self {
+            networking: self.networking.with_assistants_version(assistants_version),
+        }
+    }
+
+    /// Retry transient failures (429s, 5xx responses and connection errors) according to
+    /// `retry_policy`. Without a policy every request is attempted once.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            networking: self.networking.with_retry_policy(retry_policy),
+        }
+    }
+
+    /// Pace requests and chat completion tokens on the client side, see `RateLimiter`. Share a
+    /// clone of the same limiter between clients using the same key.
+    pub fn with_rate_limiter(self, rate_limiter: RateLimiter) -> Self {
+        Self {
+            networking: self.networking.with_rate_limiter(rate_limiter),
+        }
+    }
+
+    /// Logs request and response bodies at trace level, see `AsyncNetworking::with_body_logging`.
+    pub fn with_body_logging(self, log_bodies: bool) -> Self {
+        Self {
+            networking: self.networking.with_body_logging(log_bodies),
+        }
+    }
+
+    /// Sends requests through a custom transport, e.g. `MockTransport` in tests.
+    pub fn with_transport<T: AsyncTransport + 'static>(self, transport: T) -> Self {
+        Self {
+            networking: self.networking.with_transport(transport),
+        }
+    }
+
+    /// Returns the default model configured for the client, e.g. from a profile.
+    pub fn get_default_model(&self) -> Option<&str> {
+        self.networking.get_default_model()
+    }
+
+    /// Returns the rate limit headers, request id and processing time of the most recent response.
+    pub fn last_response_metadata(&self) -> Option<ResponseMetadata> {
+        self.networking.last_response_metadata()
+    }
+
+    /// Retrieve a client networking reference
+    pub fn netref(&self) -> &AsyncNetworking {
+        &self.networking
+    }
+
+    /* MODELS */
+    /// Load a model using the model_id.
+    pub async fn load_model<T: Identifiable>(&self, model: T) -> Result<Model, OpenApiError> {
+        let model_id: String = model.get_identifier();
+        self.networking.load_model(model_id).await
+    }
+
+    /// List all current models
+    pub async fn list_models(&self) -> Result<ApiList<Model>, OpenApiError> {
+        self.networking.list_models().await
+    }
+
+    /* FILES */
+    pub async fn list_files(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<File>, OpenApiError> {
+        self.networking.list_files(params).await
+    }
+
+    /// Iterates over every file, fetching further pages as needed.
+    pub fn iter_files(&self) -> AsyncPaginator<'_, File> {
+        let networking = &self.networking;
+        AsyncPaginator::new(move |params| {
+            Box::pin(async move { networking.list_files(Some(&params)).await })
+        })
+    }
+
+    pub async fn retrieve_file<T: Identifiable>(&self, file: T) -> Result<File, OpenApiError> {
+        let file_id: String = file.get_identifier();
+        self.networking.retrieve_file(file_id).await
+    }
+
+    pub async fn delete_file<T: Identifiable>(
+        &self,
+        file: T,
+    ) -> Result<DeletionStatus, OpenApiError> {
+        let file_id: String = file.get_identifier();
+        self.networking.delete_file(file_id).await
+    }
+
+    pub async fn retrieve_file_content<T: Identifiable>(
+        &self,
+        file: T,
+    ) -> Result<String, OpenApiError> {
+        let file_id: String = file.get_identifier();
+        self.networking.retrieve_file_content(file_id).await
+    }
+
+    /* MODERATION */
+    pub async fn create_moderation<T: Into<String>>(
+        &self,
+        text: T,
+        moderation_model: ModerationType,
+    ) -> Result<Moderation, OpenApiError> {
+        let text = text.into();
+        let moderation_model = strip_edges!(serde_json::to_string(&moderation_model)?);
+
+        let mut payload = HashMap::new();
+        payload.insert(String::from("input"), text);
+        payload.insert(String::from("model"), moderation_model);
+
+        self.networking.create_moderation(payload).await
+    }
+
+    /* ASSISTANTS */
+    pub async fn retrieve_assistant<T: Identifiable>(
+        &self,
+        assistant: T,
+    ) -> Result<Assistant, OpenApiError> {
+        let assistant_id: String = assistant.get_identifier();
+        self.networking.retrieve_assistant(assistant_id).await
+    }
+
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
+    pub async fn retrieve_assistant_file<A: Identifiable, F: Identifiable>(
+        &self,
+        assistant: A,
+        file: F,
+    ) -> Result<AssistantFile, OpenApiError> {
+        let assistant_id: String = assistant.get_identifier();
+        let file_id: String = file.get_identifier();
+        self.networking
+            .retrieve_assistant_file(assistant_id, file_id)
+            .await
+    }
+
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
+    pub async fn list_assistant_files<T: Identifiable>(
+        &self,
+        assistant: T,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<AssistantFile>, OpenApiError> {
+        let assistant_id: String = assistant.get_identifier();
+        self.networking
+            .list_assistant_files(assistant_id, params)
+            .await
+    }
+
+    pub async fn list_assistants(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Assistant>, OpenApiError> {
+        self.networking.list_assistants(params).await
+    }
+
+    /// Iterates over every assistant, fetching further pages as needed.
+    pub fn iter_assistants(&self) -> AsyncPaginator<'_, Assistant> {
+        let networking = &self.networking;
+        AsyncPaginator::new(move |params| {
+            Box::pin(async move { networking.list_assistants(Some(&params)).await })
+        })
+    }
+
+    pub async fn delete_assistant<T: Identifiable>(
+        &self,
+        assistant: T,
+    ) -> Result<DeletionStatus, OpenApiError> {
+        let assistant_id: String = assistant.get_identifier();
+        self.networking.delete_assistant(assistant_id).await
+    }
+
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
+    pub async fn delete_assistant_file<A: Identifiable, F: Identifiable>(
+        &self,
+        assistant: A,
+        file: F,
+    ) -> Result<DeletionStatus, OpenApiError> {
+        let assistant_id: String = assistant.get_identifier();
+        let file_id: String = file.get_identifier();
+        self.networking
+            .delete_assistant_file(assistant_id, file_id)
+            .await
+    }
+
+    /* THREADS */
+
+    pub async fn retrieve_thread<T: Identifiable>(
+        &self,
+        thread: T,
+    ) -> Result<Thread, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        self.networking.retrieve_thread(thread_id).await
+    }
+
+    pub async fn delete_thread<T: Identifiable>(
+        &self,
+        thread: T,
+    ) -> Result<DeletionStatus, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        self.networking.delete_thread(thread_id).await
+    }
+
+    /* MESSAGES */
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
+    #[allow(deprecated)]
+    pub async fn list_message_files<T: Identifiable, M: Identifiable>(
+        &self,
+        thread: T,
+        message: M,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<MessageFile>, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        let message_id: String = message.get_identifier();
+        self.networking
+            .list_message_files(thread_id, message_id, params)
+            .await
+    }
+
+    pub async fn list_messages<T: Identifiable>(
+        &self,
+        thread: T,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Message>, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        self.networking.list_messages(thread_id, params).await
+    }
+
+    /// Iterates over every message of `thread`, fetching further pages as needed.
+    pub fn iter_messages<T: Identifiable>(&self, thread: T) -> AsyncPaginator<'_, Message> {
+        let networking = &self.networking;
+        let thread_id: String = thread.get_identifier();
+        AsyncPaginator::new(move |params| {
+            let thread_id = thread_id.clone();
+            Box::pin(async move { networking.list_messages(thread_id, Some(&params)).await })
+        })
+    }
+
+    pub async fn retrieve_message<T: Identifiable, M: Identifiable>(
+        &self,
+        thread: T,
+        message: M,
+    ) -> Result<Message, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        let message_id: String = message.get_identifier();
+        self.networking
+            .retrieve_message(thread_id, message_id)
+            .await
+    }
+
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
+    #[allow(deprecated)]
+    pub async fn retrieve_message_file<T: Identifiable, M: Identifiable, F: Identifiable>(
+        &self,
+        thread_id: T,
+        message_id: M,
+        file_id: F,
+    ) -> Result<MessageFile, OpenApiError> {
+        let thread_id = thread_id.get_identifier();
+        let message_id = message_id.get_identifier();
+        let file_id = file_id.get_identifier();
+        self.networking
+            .retrieve_message_file(thread_id, message_id, file_id)
+            .await
+    }
+
+    /* RUNS */
+    pub async fn list_runs<T: Identifiable>(
+        &self,
+        thread: T,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Run>, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        self.networking.list_runs(thread_id, params).await
+    }
+
+    /// Iterates over every run of `thread`, fetching further pages as needed.
+    pub fn iter_runs<T: Identifiable>(&self, thread: T) -> AsyncPaginator<'_, Run> {
+        let networking = &self.networking;
+        let thread_id: String = thread.get_identifier();
+        AsyncPaginator::new(move |params| {
+            let thread_id = thread_id.clone();
+            Box::pin(async move { networking.list_runs(thread_id, Some(&params)).await })
+        })
+    }
+
+    pub async fn list_run_steps<T: Identifiable, R: Identifiable>(
+        &self,
+        thread: T,
+        run: R,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<RunStep>, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        let run_id: String = run.get_identifier();
+        self.networking
+            .list_run_steps(thread_id, run_id, params)
+            .await
+    }
+
+    pub async fn retrieve_run<T: Identifiable, R: Identifiable>(
+        &self,
+        thread: T,
+        run: R,
+    ) -> Result<Run, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        let run_id: String = run.get_identifier();
+        self.networking.retrieve_run(thread_id, run_id).await
+    }
+
+    pub async fn retrieve_run_step<T: Identifiable, R: Identifiable, S: Identifiable>(
+        &self,
+        thread_id: T,
+        run_id: R,
+        step_id: S,
+    ) -> Result<RunStep, OpenApiError> {
+        let thread_id: String = thread_id.get_identifier();
+        let run_id: String = run_id.get_identifier();
+        let step_id: String = step_id.get_identifier();
+        self.networking
+            .retrieve_run_step(thread_id, run_id, step_id)
+            .await
+    }
+
+    pub async fn cancel_run<T: Identifiable, R: Identifiable>(
+        &self,
+        thread_id: T,
+        run_id: R,
+    ) -> Result<Run, OpenApiError> {
+        let thread_id: String = thread_id.get_identifier();
+        let run_id: String = run_id.get_identifier();
+        self.networking.cancel_run(thread_id, run_id).await
+    }
+}
@@ -1,8 +1,7 @@
 use rust_open_ai::assistant::AssistantBuilder;
 use rust_open_ai::file::{FileBuilder, FileTypes};
-use rust_open_ai::message::{MessageBuilder, MessageRole};
+use rust_open_ai::message::MessageBuilder;
 use rust_open_ai::moderation::ModerationType;
-use rust_open_ai::run::RunBuilder;
 use rust_open_ai::thread::ThreadBuilder;
 use rust_open_ai::OpenAIClient;
 
@@ -15,48 +14,54 @@ fn main() {
 
     // Model
     let list_models = client.list_models().unwrap();
+    println!("{:?}", list_models);
     let load_model = client.load_model("gpt-4").unwrap();
 
     // File
-    let listed_files = client.list_files().unwrap();
+    let listed_files = client.list_files(None).unwrap();
+    println!("{:?}", listed_files);
     let uploaded_file = FileBuilder::new("src/bin/upload_example.pdf", FileTypes::Assistants)
         .build(client.netref())
         .unwrap();
     let get_file = client.retrieve_file(&uploaded_file).unwrap();
+    println!("{:?}", get_file);
     //let get_content = client.retrieve_file_content(&uploaded_file).unwrap();
     let del_file = client.delete_file(&uploaded_file).unwrap();
+    println!("{:?}", del_file);
 
     // Moderation
     let moderation = client
         .create_moderation("message content", ModerationType::Latest)
         .unwrap();
+    println!("{:?}", moderation);
 
     // Assistant
-    let assistants = client.list_assistants().unwrap();
+    let assistants = client.list_assistants(None).unwrap();
+    println!("{:?}", assistants);
     let new_assistant = AssistantBuilder::new(&load_model)
         .build(client.netref())
         .unwrap();
     let get_assistant = client.retrieve_assistant(&new_assistant).unwrap();
-    let assistant_files = client.list_assistant_files(&get_assistant).unwrap();
     let del_assistant = client.delete_assistant(&get_assistant).unwrap();
+    println!("{:?}", del_assistant);
 
     // Thread
     let new_thread = ThreadBuilder::new().build(client.netref()).unwrap();
     let get_thread = client.retrieve_thread(&new_thread).unwrap();
-    // modify thread
+    println!("{:?}", get_thread);
     let del_thread = client.delete_thread(&new_thread).unwrap();
+    println!("{:?}", del_thread);
 
     // Message
     let msg_thread = ThreadBuilder::new().build(client.netref()).unwrap();
-    let messages = client.list_messages(&msg_thread).unwrap();
+    let messages = client.list_messages(&msg_thread, None).unwrap();
+    println!("{:?}", messages);
     let new_msg = MessageBuilder::new(&msg_thread, "Insert message here...".to_string())
         .unwrap()
         .build(client.netref())
         .unwrap();
     let get_msg = client.retrieve_message(&msg_thread, &new_msg).unwrap();
-    let msg_files = client.list_message_files(&msg_thread, &get_msg).unwrap();
-    //let msg_file = client.retrieve_message_file(&msg_thread, &get_msg, "file_id").unwrap();
-    //modify message
+    println!("{:?}", get_msg);
 
     // Run
     //let new_run = RunBuilder::new()
@@ -1,11 +1,17 @@
-use crate::moderation::{ModerationCategories, ModerationType};
+use crate::moderation::ModerationType;
 use std::collections::HashMap;
-use std::env;
 
-use crate::networking::Networking;
+use crate::client_builder::OpenAIClientBuilder;
+use crate::credentials;
+use crate::networking::transport::Transport;
+use crate::networking::{
+    AssistantActions, AssistantsVersion, AzureConfig, FileActions, KeyPool, MessageActions,
+    ModelActions, Networking, NetworkingConfig, Paginator, RateLimiter, ResponseMetadata,
+    RetryPolicy, RunActions, ThreadActions,
+};
 use crate::strip_edges;
 use crate::types::assistant::{Assistant, AssistantFile};
-use crate::types::common::{ApiList, DeletionStatus, Identifiable};
+use crate::types::common::{ApiList, DeletionStatus, Identifiable, ListParams};
 use crate::types::error::OpenApiError;
 use crate::types::file::File;
 use crate::types::message::{Message, MessageFile};
@@ -22,8 +28,13 @@ use crate::types::thread::Thread;
 /// - `networking`: A `Networking` struct that handles all networking with the API using the reqwest crate
 ///
 /// ## Examples
-/// ```
-/// todo!()
+/// ```no_run
+/// # use rust_open_ai::OpenAIClient;
+/// # fn example() -> Result<(), rust_open_ai::error::OpenApiError> {
+/// let client = OpenAIClient::new("sk-...", None)?;
+/// let models = client.list_models()?;
+/// # Ok(())
+/// # }
 /// ```
 ///
 /// # Errors
@@ -37,55 +48,170 @@ pub struct OpenAIClient {
 impl OpenAIClient {
     /// Attempts to pull OPEN_API_KEY from ENV
     pub fn obtain_key() -> Result<String, OpenApiError> {
-        env::var("OPENAI_API_KEY").map_err(|e| OpenApiError::ClientError(e.to_string()))
+        credentials::obtain_key()
     }
 
-    /// Prompts user for OPEN_API_KEY value, then sets it in the ENV
+    /// Prompts user for an api key on stdin. The environment is left untouched, as mutating it is
+    /// unsafe in multithreaded programs.
     pub fn prompt_key() -> Result<String, OpenApiError> {
-        // Request user input
-        let mut key_buff = String::new();
-        println!("Please enter your OpenAI API key: ");
-        std::io::stdin()
-            .read_line(&mut key_buff)
-            .map_err(|e| OpenApiError::ClientError(e.to_string()))?;
-
-        // Set env and return key
-        let trimmed_key = key_buff.trim();
-        env::set_var("OPENAI_API_KEY", trimmed_key);
-        Ok(trimmed_key.to_string())
+        credentials::prompt_key()
     }
 
     /// Generate a new OpenAIClient supplying both the Apikey and Organization ID
+    ///
+    /// The base url is read from `OPENAI_BASE_URL` when set, otherwise the OpenAI api is used.
     pub fn new<A: Into<String>>(
         apikey: A,
         organization_id: Option<String>,
     ) -> Result<Self, OpenApiError> {
         let apikey = apikey.into();
+        let base_url = NetworkingConfig::base_url_from_env()?;
+        Ok(Self {
+            networking: Networking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
+        })
+    }
+
+    /// Generate a new OpenAIClient targeting an OpenAI compatible server at `base_url`, e.g.
+    /// `http://localhost:8000/v1`. Endpoints are resolved relative to the base path.
+    pub fn new_with_base_url<A: Into<String>, U: AsRef<str>>(
+        apikey: A,
+        organization_id: Option<String>,
+        base_url: U,
+    ) -> Result<Self, OpenApiError> {
+        let apikey = apikey.into();
+        let base_url = NetworkingConfig::parse_base_url(base_url)?;
         Ok(Self {
-            networking: Networking::new(apikey, organization_id.clone()),
+            networking: Networking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
         })
     }
 
+    /// Generate a new OpenAIClient for Azure OpenAI, where `endpoint` is the resource endpoint
+    /// (e.g. `https://{resource}.openai.azure.com/`) and `azure` holds the api version and the
+    /// model to deployment table.
+    pub fn new_azure<A: Into<String>, U: AsRef<str>>(
+        apikey: A,
+        endpoint: U,
+        azure: AzureConfig,
+    ) -> Result<Self, OpenApiError> {
+        let apikey = apikey.into();
+        let base_url = NetworkingConfig::parse_base_url(endpoint)?;
+        Ok(Self {
+            networking: Networking::new(
+                NetworkingConfig::new(apikey, None, base_url)?.with_azure(azure),
+            ),
+        })
+    }
+
+    /// Wraps a `Networking` configured by `OpenAIClientBuilder`.
+    pub(crate) fn from_networking(networking: Networking) -> Self {
+        Self { networking }
+    }
+
+    /// Generate a new OpenAIClient from the profile `name` of the config file, by default
+    /// `~/.config/rust_open_ai/config.toml`. Environment variables override the profile.
+    pub fn from_profile(name: &str) -> Result<Self, OpenApiError> {
+        OpenAIClientBuilder::from_profile(name)?.build()
+    }
+
+    /// Generate a new OpenAIClient distributing requests over the keys of `key_pool`, see `KeyPool`.
+    ///
+    /// The base url is read from `OPENAI_BASE_URL` when set, otherwise the OpenAI api is used.
+    pub fn new_with_key_pool(key_pool: KeyPool) -> Result<Self, OpenApiError> {
+        if key_pool.is_empty() {
+            return Err(OpenApiError::ClientError(String::from(
+                "Key pool must contain at least one key",
+            )));
+        }
+        let base_url = NetworkingConfig::base_url_from_env()?;
+        let config = NetworkingConfig::new("", None, base_url)?.with_key_pool(key_pool);
+        Ok(Self::from_networking(Networking::new(config)))
+    }
+
     /// Generate a new OpenAIClient and if apikey is not found return an error
     pub fn new_with_env(organization_id: Option<String>) -> Result<Self, OpenApiError> {
         let apikey = OpenAIClient::obtain_key()?;
+        let base_url = NetworkingConfig::base_url_from_env()?;
         Ok(Self {
-            networking: Networking::new(apikey, organization_id.clone()),
+            networking: Networking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
         })
     }
 
-    /// Generate a new OpenAIClient and if apikey is not found, prompt user for key and set env.
+    /// Generate a new OpenAIClient and if apikey is not found, prompt user for key.
     pub fn new_with_prompt(organization_id: Option<String>) -> Result<Self, OpenApiError> {
         let mut apikey = OpenAIClient::obtain_key();
         if apikey.is_err() {
             apikey = OpenAIClient::prompt_key()
         }
         let apikey = apikey?;
+        let base_url = NetworkingConfig::base_url_from_env()?;
         Ok(Self {
-            networking: Networking::new(apikey, organization_id.clone()),
+            networking: Networking::new(NetworkingConfig::new(
+                apikey,
+                organization_id.clone(),
+                base_url,
+            )?),
         })
     }
 
+    /// Sets the Assistants api version sent as `OpenAI-Beta`, by default `AssistantsVersion::V2`.
+    pub fn with_assistants_version(self, assistants_version: AssistantsVersion) -> Self {
+        Self {
+            networking: self.networking.with_assistants_version(assistants_version),
+        }
+    }
+
+    /// Retry transient failures (429s, 5xx responses and connection errors) according to
+    /// `retry_policy`. Without a policy every request is attempted once.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            networking: self.networking.with_retry_policy(retry_policy),
+        }
+    }
+
+    /// Pace requests and chat completion tokens on the client side, see `RateLimiter`. Share a
+    /// clone of the same limiter between clients using the same key.
+    pub fn with_rate_limiter(self, rate_limiter: RateLimiter) -> Self {
+        Self {
+            networking: self.networking.with_rate_limiter(rate_limiter),
+        }
+    }
+
+    /// Logs request and response bodies at trace level, see `Networking::with_body_logging`.
+    pub fn with_body_logging(self, log_bodies: bool) -> Self {
+        Self {
+            networking: self.networking.with_body_logging(log_bodies),
+        }
+    }
+
+    /// Sends requests through a custom transport, e.g. `MockTransport` in tests.
+    pub fn with_transport<T: Transport + 'static>(self, transport: T) -> Self {
+        Self {
+            networking: self.networking.with_transport(transport),
+        }
+    }
+
+    /// Returns the default model configured for the client, e.g. from a profile.
+    pub fn get_default_model(&self) -> Option<&str> {
+        self.networking.get_default_model()
+    }
+
+    /// Returns the rate limit headers, request id and processing time of the most recent response.
+    pub fn last_response_metadata(&self) -> Option<ResponseMetadata> {
+        self.networking.last_response_metadata()
+    }
+
     /// Retrieve a client networking reference
     pub fn netref(&self) -> &Networking {
         &self.networking
@@ -104,8 +230,13 @@ impl OpenAIClient {
     }
 
     /* FILES */
-    pub fn list_files(&self) -> Result<ApiList<File>, OpenApiError> {
-        self.networking.list_files()
+    pub fn list_files(&self, params: Option<&ListParams>) -> Result<ApiList<File>, OpenApiError> {
+        self.networking.list_files(params)
+    }
+
+    /// Iterates over every file, fetching further pages as needed.
+    pub fn iter_files(&self) -> Paginator<'_, File> {
+        Paginator::new(move |params| self.networking.list_files(Some(params)))
     }
 
     pub fn retrieve_file<T: Identifiable>(&self, file: T) -> Result<File, OpenApiError> {
@@ -148,6 +279,10 @@ impl OpenAIClient {
         self.networking.retrieve_assistant(assistant_id)
     }
 
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn retrieve_assistant_file<A: Identifiable, F: Identifiable>(
         &self,
         assistant: A,
@@ -159,16 +294,29 @@ impl OpenAIClient {
             .retrieve_assistant_file(assistant_id, file_id)
     }
 
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn list_assistant_files<T: Identifiable>(
         &self,
         assistant: T,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<AssistantFile>, OpenApiError> {
         let assistant_id: String = assistant.get_identifier();
-        self.networking.list_assistant_files(assistant_id)
+        self.networking.list_assistant_files(assistant_id, params)
     }
 
-    pub fn list_assistants(&self) -> Result<ApiList<Assistant>, OpenApiError> {
-        self.networking.list_assistants(None) //TODO Check params of list assistant
+    pub fn list_assistants(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Assistant>, OpenApiError> {
+        self.networking.list_assistants(params)
+    }
+
+    /// Iterates over every assistant, fetching further pages as needed.
+    pub fn iter_assistants(&self) -> Paginator<'_, Assistant> {
+        Paginator::new(move |params| self.networking.list_assistants(Some(params)))
     }
 
     pub fn delete_assistant<T: Identifiable>(
@@ -179,6 +327,10 @@ impl OpenAIClient {
         self.networking.delete_assistant(assistant_id)
     }
 
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn delete_assistant_file<A: Identifiable, F: Identifiable>(
         &self,
         assistant: A,
@@ -205,22 +357,38 @@ impl OpenAIClient {
     }
 
     /* MESSAGES */
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
+    #[allow(deprecated)]
     pub fn list_message_files<T: Identifiable, M: Identifiable>(
         &self,
         thread: T,
         message: M,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<MessageFile>, OpenApiError> {
         let thread_id: String = thread.get_identifier();
         let message_id: String = message.get_identifier();
-        self.networking.list_message_files(thread_id, message_id)
+        self.networking
+            .list_message_files(thread_id, message_id, params)
     }
 
     pub fn list_messages<T: Identifiable>(
         &self,
         thread: T,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<Message>, OpenApiError> {
         let thread_id: String = thread.get_identifier();
-        self.networking.list_messages(thread_id)
+        self.networking.list_messages(thread_id, params)
+    }
+
+    /// Iterates over every message of `thread`, fetching further pages as needed.
+    pub fn iter_messages<T: Identifiable>(&self, thread: T) -> Paginator<'_, Message> {
+        let thread_id: String = thread.get_identifier();
+        Paginator::new(move |params| {
+            self.networking
+                .list_messages(thread_id.clone(), Some(params))
+        })
     }
 
     pub fn retrieve_message<T: Identifiable, M: Identifiable>(
@@ -233,6 +401,10 @@ impl OpenAIClient {
         self.networking.retrieve_message(thread_id, message_id)
     }
 
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
+    #[allow(deprecated)]
     pub fn retrieve_message_file<T: Identifiable, M: Identifiable, F: Identifiable>(
         &self,
         thread_id: T,
@@ -247,6 +419,32 @@ impl OpenAIClient {
     }
 
     /* RUNS */
+    pub fn list_runs<T: Identifiable>(
+        &self,
+        thread: T,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Run>, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        self.networking.list_runs(thread_id, params)
+    }
+
+    /// Iterates over every run of `thread`, fetching further pages as needed.
+    pub fn iter_runs<T: Identifiable>(&self, thread: T) -> Paginator<'_, Run> {
+        let thread_id: String = thread.get_identifier();
+        Paginator::new(move |params| self.networking.list_runs(thread_id.clone(), Some(params)))
+    }
+
+    pub fn list_run_steps<T: Identifiable, R: Identifiable>(
+        &self,
+        thread: T,
+        run: R,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<RunStep>, OpenApiError> {
+        let thread_id: String = thread.get_identifier();
+        let run_id: String = run.get_identifier();
+        self.networking.list_run_steps(thread_id, run_id, params)
+    }
+
     pub fn retrieve_run<T: Identifiable, R: Identifiable>(
         &self,
         thread: T,
@@ -281,5 +479,17 @@ impl OpenAIClient {
     }
 }
 
-#[cfg(tests)]
-mod tests {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_header_values_rejected() {
+        assert!(OpenAIClient::new("sk-test\n", None).is_err());
+        assert!(OpenAIClient::new("sk-test", Some(String::from("org\nX-Injected: 1"))).is_err());
+        let azure = AzureConfig::new("2024-06-01");
+        assert!(OpenAIClient::new_azure("key\n", "https://res.openai.azure.com/", azure).is_err());
+        let pool = KeyPool::new().add_key("sk-test", None).unwrap();
+        assert!(OpenAIClient::new_with_key_pool(pool).is_ok());
+    }
+}
@@ -0,0 +1,338 @@
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::Url;
+
+use crate::credentials;
+use crate::credentials::ApiKey;
+use crate::networking::{
+    AssistantsVersion, AzureConfig, KeyPool, NetworkingConfig, RateLimiter, RetryPolicy,
+};
+use crate::profile::Config;
+use crate::types::error::OpenApiError;
+
+#[cfg(feature = "async")]
+use crate::networking::transport::AsyncReqwestTransport;
+#[cfg(feature = "blocking")]
+use crate::networking::transport::ReqwestTransport;
+#[cfg(feature = "async")]
+use crate::networking::AsyncNetworking;
+#[cfg(feature = "blocking")]
+use crate::networking::Networking;
+#[cfg(feature = "async")]
+use crate::AsyncOpenAIClient;
+#[cfg(feature = "blocking")]
+use crate::OpenAIClient;
+
+/// User agent sent when none is configured.
+const DEFAULT_USER_AGENT: &str = concat!("rust_open_ai/", env!("CARGO_PKG_VERSION"));
+
+/// `OpenAIClientBuilder` configures the HTTP client behind an `OpenAIClient`, for settings the
+/// plain constructors do not cover such as timeouts, proxies and custom root certificates.
+///
+/// # Fields
+///
+/// * `apikey: Option<ApiKey>` - The api key, read from `OPENAI_API_KEY` when not supplied.
+/// * `organization_id: Option<String>` - The organization sent as `OpenAI-Organization`.
+/// * `base_url: Option<String>` - The base url, read from `OPENAI_BASE_URL` when not supplied.
+/// * `default_model: Option<String>` - The model used by default, see `get_default_model` on the client.
+/// * `azure: Option<AzureConfig>` - Switches the client into Azure OpenAI mode, see `AzureConfig`.
+/// * `assistants_version: AssistantsVersion` - The Assistants api version sent as `OpenAI-Beta`, by default v2.
+/// * `retry_policy: RetryPolicy` - The policy for retrying transient failures, by default none.
+/// * `rate_limiter: Option<RateLimiter>` - The client side limiter pacing requests, by default none.
+/// * `key_pool: Option<KeyPool>` - A pool of keys used in place of `apikey`, see `KeyPool`.
+/// * `connect_timeout: Option<Duration>` - The timeout for establishing a connection.
+/// * `timeout: Option<Duration>` - The timeout for a whole request, from connecting until the response body has been read.
+/// * `proxy: Option<String>` - The url of a proxy every request is routed through, e.g. `http://egress.internal:3128`.
+/// * `root_certificates: Vec<PathBuf>` - PEM encoded certificates trusted in addition to the system roots.
+/// * `user_agent: String` - The `User-Agent` header, by default `rust_open_ai/{version}`.
+/// * `extra_headers: Vec<(String, String)>` - Headers sent with every request, e.g. `OpenAI-Project`.
+///
+/// # Examples
+/// ```ignore
+/// let client = OpenAIClientBuilder::new()
+///     .with_apikey(apikey)
+///     .with_project("proj_abc123")
+///     .with_connect_timeout(Duration::from_secs(5))
+///     .with_timeout(Duration::from_secs(60))
+///     .with_proxy("http://egress.internal:3128")
+///     .build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenAIClientBuilder {
+    apikey: Option<ApiKey>,
+    organization_id: Option<String>,
+    base_url: Option<String>,
+    default_model: Option<String>,
+    azure: Option<AzureConfig>,
+    assistants_version: AssistantsVersion,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    key_pool: Option<KeyPool>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    proxy: Option<String>,
+    root_certificates: Vec<PathBuf>,
+    user_agent: String,
+    extra_headers: Vec<(String, String)>,
+}
+
+impl Default for OpenAIClientBuilder {
+    fn default() -> Self {
+        Self {
+            apikey: None,
+            organization_id: None,
+            base_url: None,
+            default_model: None,
+            azure: None,
+            assistants_version: AssistantsVersion::default(),
+            retry_policy: RetryPolicy::none(),
+            rate_limiter: None,
+            key_pool: None,
+            connect_timeout: None,
+            timeout: None,
+            proxy: None,
+            root_certificates: Vec::new(),
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            extra_headers: Vec::new(),
+        }
+    }
+}
+
+impl OpenAIClientBuilder {
+    pub fn new() -> Self {
+        OpenAIClientBuilder::default()
+    }
+
+    /// A builder configured from the profile `name` of the config file, with environment
+    /// overrides applied, see `Config`.
+    pub fn from_profile(name: &str) -> Result<Self, OpenApiError> {
+        Config::load()?
+            .get_profile(name)?
+            .clone()
+            .with_env_overrides(|name| env::var(name).ok())
+            .to_builder()
+    }
+
+    pub fn with_apikey<A: Into<String>>(mut self, apikey: A) -> Self {
+        self.apikey = Some(ApiKey::from(apikey.into()));
+        self
+    }
+
+    pub(crate) fn with_resolved_key(mut self, apikey: ApiKey) -> Self {
+        self.apikey = Some(apikey);
+        self
+    }
+
+    pub fn with_organization_id<O: Into<String>>(mut self, organization_id: O) -> Self {
+        self.organization_id = Some(organization_id.into());
+        self
+    }
+
+    /// Scopes requests to a project through the `OpenAI-Project` header.
+    pub fn with_project<P: Into<String>>(self, project_id: P) -> Self {
+        self.with_header("OpenAI-Project", project_id)
+    }
+
+    pub fn with_base_url<U: Into<String>>(mut self, base_url: U) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn with_default_model<M: Into<String>>(mut self, default_model: M) -> Self {
+        self.default_model = Some(default_model.into());
+        self
+    }
+
+    /// Switches to Azure OpenAI mode, the base url should be the Azure resource endpoint.
+    pub fn with_azure(mut self, azure: AzureConfig) -> Self {
+        self.azure = Some(azure);
+        self
+    }
+
+    pub fn with_assistants_version(mut self, assistants_version: AssistantsVersion) -> Self {
+        self.assistants_version = assistants_version;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Distributes requests over the keys of `key_pool`, in which case `apikey` is not required.
+    pub fn with_key_pool(mut self, key_pool: KeyPool) -> Self {
+        self.key_pool = Some(key_pool);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_proxy<P: Into<String>>(mut self, proxy: P) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Trusts the PEM encoded certificate at `path`, e.g. the root of a TLS intercepting gateway.
+    pub fn add_root_certificate<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.root_certificates.push(path.into());
+        self
+    }
+
+    pub fn with_user_agent<U: Into<String>>(mut self, user_agent: U) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Adds a header sent with every request, replacing any default header of the same name.
+    pub fn with_header<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        let name = name.into();
+        self.extra_headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(&name));
+        self.extra_headers.push((name, value.into()));
+        self
+    }
+
+    /// Validates the settings shared by the blocking and async clients.
+    pub(crate) fn build_config(&self) -> Result<NetworkingConfig, OpenApiError> {
+        let apikey = match (&self.apikey, &self.key_pool) {
+            (Some(apikey), _) => apikey.clone(),
+            (None, Some(_)) => ApiKey::default(),
+            (None, None) => ApiKey::from(credentials::obtain_key()?),
+        };
+        let base_url = match &self.base_url {
+            Some(base_url) => NetworkingConfig::parse_base_url(base_url)?,
+            None => NetworkingConfig::base_url_from_env()?,
+        };
+
+        let mut config = NetworkingConfig::new(apikey, self.organization_id.clone(), base_url)?
+            .with_assistants_version(self.assistants_version)
+            .with_retry_policy(self.retry_policy.clone())
+            .with_extra_headers(self.extra_headers.clone())?;
+        if let Some(azure) = &self.azure {
+            config = config.with_azure(azure.clone());
+        }
+        if let Some(default_model) = &self.default_model {
+            config = config.with_default_model(default_model.clone());
+        }
+        if let Some(key_pool) = &self.key_pool {
+            config = config.with_key_pool(key_pool.clone());
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            config = config.with_rate_limiter(rate_limiter.clone());
+        }
+        Ok(config)
+    }
+
+    fn proxy(&self) -> Result<Option<reqwest::Proxy>, OpenApiError> {
+        match &self.proxy {
+            Some(proxy) => Ok(Some(reqwest::Proxy::all(Url::parse(proxy)?)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn root_certificates(&self) -> Result<Vec<reqwest::Certificate>, OpenApiError> {
+        self.root_certificates
+            .iter()
+            .map(|path| Ok(reqwest::Certificate::from_pem(&std::fs::read(path)?)?))
+            .collect()
+    }
+
+    /// Builds a blocking `OpenAIClient`.
+    #[cfg(feature = "blocking")]
+    pub fn build(self) -> Result<OpenAIClient, OpenApiError> {
+        let config = self.build_config()?;
+        let mut client = reqwest::blocking::Client::builder().user_agent(self.user_agent.clone());
+        if let Some(connect_timeout) = self.connect_timeout {
+            client = client.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy()? {
+            client = client.proxy(proxy);
+        }
+        for certificate in self.root_certificates()? {
+            client = client.add_root_certificate(certificate);
+        }
+        let transport = ReqwestTransport::new(client.build()?);
+        Ok(OpenAIClient::from_networking(
+            Networking::new(config).with_transport(transport),
+        ))
+    }
+
+    /// Builds an `AsyncOpenAIClient`.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<AsyncOpenAIClient, OpenApiError> {
+        let config = self.build_config()?;
+        let mut client = reqwest::Client::builder().user_agent(self.user_agent.clone());
+        if let Some(connect_timeout) = self.connect_timeout {
+            client = client.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy()? {
+            client = client.proxy(proxy);
+        }
+        for certificate in self.root_certificates()? {
+            client = client.add_root_certificate(certificate);
+        }
+        let transport = AsyncReqwestTransport::new(client.build()?);
+        Ok(AsyncOpenAIClient::from_networking(
+            AsyncNetworking::new(config).with_transport(transport),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_headers_sent() {
+        let config = OpenAIClientBuilder::new()
+            .with_apikey("sk-test")
+            .with_base_url("http://localhost:8000/v1")
+            .with_project("proj_old")
+            .with_project("proj_abc")
+            .build_config()
+            .unwrap();
+        let headers = config.construct_headers(None).unwrap();
+        assert_eq!(headers.get("OpenAI-Project").unwrap(), "proj_abc");
+        assert_eq!(headers.get("Authorization").unwrap(), "Bearer sk-test");
+        assert_eq!(headers.get("OpenAI-Beta").unwrap(), "assistants=v2");
+    }
+
+    #[test]
+    fn test_invalid_header_rejected() {
+        let result = OpenAIClientBuilder::new()
+            .with_apikey("sk-test")
+            .with_header("OpenAI-Project", "line\nbreak")
+            .build_config();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_organization_rejected() {
+        let result = OpenAIClientBuilder::new()
+            .with_apikey("sk-test")
+            .with_organization_id("org-abc\nX-Injected: 1")
+            .build_config();
+        assert!(matches!(result, Err(OpenApiError::ClientError(_))));
+    }
+}
@@ -0,0 +1,74 @@
+use std::env;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::types::error::OpenApiError;
+
+/// Attempts to pull OPEN_API_KEY from ENV
+pub(crate) fn obtain_key() -> Result<String, OpenApiError> {
+    env::var("OPENAI_API_KEY").map_err(|e| OpenApiError::ClientError(e.to_string()))
+}
+
+/// Prompts user for an api key on stdin. The key is not written to the environment, as
+/// `env::set_var` is unsafe while other threads may read the environment.
+pub(crate) fn prompt_key() -> Result<String, OpenApiError> {
+    // Request user input
+    let mut key_buff = String::new();
+    println!("Please enter your OpenAI API key: ");
+    std::io::stdin()
+        .read_line(&mut key_buff)
+        .map_err(|e| OpenApiError::ClientError(e.to_string()))?;
+
+    Ok(key_buff.trim().to_string())
+}
+
+/// Secret wrapper holding an api key. The key is redacted from `Debug` output and is never
+/// serialized, though it may be deserialized from a config file. The only place it is read is
+/// when constructing the request headers.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub(crate) struct ApiKey(String);
+
+impl ApiKey {
+    /// Exposes the key material, only to be used when authenticating a request.
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// A redacted form of the key safe to log, e.g. `sk-...wxyz`.
+    pub(crate) fn hint(&self) -> String {
+        let chars: Vec<char> = self.0.chars().collect();
+        match chars.len() {
+            0..=11 => String::from("<redacted>"),
+            len => format!(
+                "{}...{}",
+                chars[..3].iter().collect::<String>(),
+                chars[len - 4..].iter().collect::<String>()
+            ),
+        }
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(value: String) -> Self {
+        ApiKey(value)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(value: &str) -> Self {
+        ApiKey(value.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(ApiKey)
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(<redacted>)")
+    }
+}
@@ -9,13 +9,40 @@
 /// - builders - Builder objects used for creating new objects
 /// -
 ///
+// Lets the code generated by `rust_open_ai_derive` refer to `::rust_open_ai` within this crate
+extern crate self as rust_open_ai;
+
+#[cfg(feature = "blocking")]
 mod client;
+#[cfg(feature = "blocking")]
 pub use client::OpenAIClient;
 
+#[cfg(feature = "async")]
+mod async_client;
+#[cfg(feature = "async")]
+pub use async_client::AsyncOpenAIClient;
+
+mod client_builder;
+pub use client_builder::OpenAIClientBuilder;
+
+mod credentials;
 mod macros;
 mod networking;
+mod profile;
+pub use profile::{Config, Profile};
 mod types;
 
+pub use networking::transport;
+pub use networking::{
+    AssistantsVersion, AzureConfig, KeyPool, KeyStats, PoolStrategy, RateLimit, RateLimiter,
+    ResponseMetadata, RetryPolicy,
+};
+
+#[cfg(feature = "async")]
+pub use networking::{AsyncChatCompletionStream, AsyncPaginator};
+#[cfg(feature = "blocking")]
+pub use networking::{ChatCompletionStream, Paginator};
+
 pub use types::assistant;
 pub use types::chat;
 pub use types::common;
@@ -25,12 +52,18 @@ pub use types::message;
 pub use types::model;
 pub use types::moderation;
 pub use types::run;
+pub use types::schema;
 pub use types::thread;
+pub use types::tool;
+
+/// Dependencies of the code generated by `rust_open_ai_derive`, not part of the public api.
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+}
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4); //TODO
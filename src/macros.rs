@@ -1,5 +1,3 @@
-use serde_json;
-
 /// Simplifies the implementation of a trait for a type in the case where the reference
 /// implementation is the same as the owned implementation.
 #[macro_export]
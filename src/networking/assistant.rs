@@ -1,33 +1,44 @@
 use crate::assistant::{Assistant, AssistantBuilder, AssistantFile, AssistantFileBuilder};
-use crate::common::{ApiList, DeletionStatus};
+use crate::common::{ApiList, DeletionStatus, ListParams};
 use crate::error::OpenApiError;
 use crate::networking::Networking;
 use reqwest::Method;
 
 pub trait AssistantActions {
     fn create_assistant(&self, payload: &AssistantBuilder) -> Result<Assistant, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
     fn create_assistant_file(
         &self,
         payload: &AssistantFileBuilder,
-        assistant_id: &String,
+        assistant_id: &str,
     ) -> Result<AssistantFile, OpenApiError>;
-    fn list_assistants(&self, params: Option<&String>) -> Result<ApiList<Assistant>, OpenApiError>;
+    fn list_assistants(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Assistant>, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
     fn list_assistant_files(
         &self,
         assistant_id: String,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<AssistantFile>, OpenApiError>;
     fn retrieve_assistant(&self, assistant_id: String) -> Result<Assistant, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
     fn retrieve_assistant_file(
         &self,
         assistant_id: String,
         file_id: String,
     ) -> Result<AssistantFile, OpenApiError>;
-    fn modify_assistant(
-        &self,
-        assistant_id: String,
-        payload: &AssistantBuilder,
-    ) -> Result<Assistant, OpenApiError>;
     fn delete_assistant(&self, assistant_id: String) -> Result<DeletionStatus, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
     fn delete_assistant_file(
         &self,
         assistant_id: String,
@@ -48,7 +59,7 @@ impl AssistantActions for Networking {
     fn create_assistant_file(
         &self,
         payload: &AssistantFileBuilder,
-        assistant_id: &String,
+        assistant_id: &str,
     ) -> Result<AssistantFile, OpenApiError> {
         self.send_and_convert(
             Method::POST,
@@ -58,18 +69,26 @@ impl AssistantActions for Networking {
         )
     }
 
-    fn list_assistants(&self, params: Option<&String>) -> Result<ApiList<Assistant>, OpenApiError> {
-        // Add param support
-        self.send_and_convert(Method::GET, String::from("assistants"), None, None)
+    fn list_assistants(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Assistant>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(String::from("assistants"), params)?,
+            None,
+            None,
+        )
     }
 
     fn list_assistant_files(
         &self,
         assistant_id: String,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<AssistantFile>, OpenApiError> {
         self.send_and_convert(
             Method::GET,
-            format!("assistants/{}/files", assistant_id),
+            ListParams::apply(format!("assistants/{}/files", assistant_id), params)?,
             None,
             None,
         )
@@ -97,19 +116,6 @@ impl AssistantActions for Networking {
         )
     }
 
-    fn modify_assistant(
-        &self,
-        assistant_id: String,
-        payload: &AssistantBuilder,
-    ) -> Result<Assistant, OpenApiError> {
-        self.send_and_convert(
-            Method::PATCH,
-            format!("assistants/{}", assistant_id),
-            Some(serde_json::to_value(payload)?),
-            None,
-        )
-    }
-
     fn delete_assistant(&self, assistant_id: String) -> Result<DeletionStatus, OpenApiError> {
         self.send_and_convert(
             Method::DELETE,
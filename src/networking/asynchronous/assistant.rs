@@ -0,0 +1,155 @@
+use crate::assistant::{Assistant, AssistantBuilder, AssistantFile, AssistantFileBuilder};
+use crate::common::{ApiList, DeletionStatus, ListParams};
+use crate::error::OpenApiError;
+use crate::networking::AsyncNetworking;
+use async_trait::async_trait;
+use reqwest::Method;
+
+#[async_trait]
+pub trait AsyncAssistantActions {
+    async fn create_assistant(&self, payload: &AssistantBuilder)
+        -> Result<Assistant, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    async fn create_assistant_file(
+        &self,
+        payload: &AssistantFileBuilder,
+        assistant_id: &str,
+    ) -> Result<AssistantFile, OpenApiError>;
+    async fn list_assistants(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Assistant>, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    async fn list_assistant_files(
+        &self,
+        assistant_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<AssistantFile>, OpenApiError>;
+    async fn retrieve_assistant(&self, assistant_id: String) -> Result<Assistant, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    async fn retrieve_assistant_file(
+        &self,
+        assistant_id: String,
+        file_id: String,
+    ) -> Result<AssistantFile, OpenApiError>;
+    async fn delete_assistant(&self, assistant_id: String) -> Result<DeletionStatus, OpenApiError>;
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    async fn delete_assistant_file(
+        &self,
+        assistant_id: String,
+        file_id: String,
+    ) -> Result<DeletionStatus, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncAssistantActions for AsyncNetworking {
+    async fn create_assistant(
+        &self,
+        payload: &AssistantBuilder,
+    ) -> Result<Assistant, OpenApiError> {
+        self.send_and_convert(
+            Method::POST,
+            String::from("assistants"),
+            Some(serde_json::to_value(payload)?),
+            None,
+        )
+        .await
+    }
+
+    async fn create_assistant_file(
+        &self,
+        payload: &AssistantFileBuilder,
+        assistant_id: &str,
+    ) -> Result<AssistantFile, OpenApiError> {
+        self.send_and_convert(
+            Method::POST,
+            format!("assistants/{}/files", assistant_id),
+            Some(serde_json::to_value(payload)?),
+            None,
+        )
+        .await
+    }
+
+    async fn list_assistants(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Assistant>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(String::from("assistants"), params)?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn list_assistant_files(
+        &self,
+        assistant_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<AssistantFile>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(format!("assistants/{}/files", assistant_id), params)?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_assistant(&self, assistant_id: String) -> Result<Assistant, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!("assistants/{}", assistant_id),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_assistant_file(
+        &self,
+        assistant_id: String,
+        file_id: String,
+    ) -> Result<AssistantFile, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!("assistants/{}/files/{}", assistant_id, file_id),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn delete_assistant(&self, assistant_id: String) -> Result<DeletionStatus, OpenApiError> {
+        self.send_and_convert(
+            Method::DELETE,
+            format!("assistants/{}", assistant_id),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn delete_assistant_file(
+        &self,
+        assistant_id: String,
+        file_id: String,
+    ) -> Result<DeletionStatus, OpenApiError> {
+        self.send_and_convert(
+            Method::DELETE,
+            format!("assistants/{}/files/{}", assistant_id, file_id),
+            None,
+            None,
+        )
+        .await
+    }
+}
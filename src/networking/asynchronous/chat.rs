@@ -0,0 +1,55 @@
+use crate::chat::{ChatCompletion, ChatCompletionBuilder};
+use crate::error::OpenApiError;
+use crate::networking::{AsyncChatCompletionStream, AsyncNetworking};
+use async_trait::async_trait;
+use reqwest::Method;
+
+#[async_trait]
+pub trait AsyncChatCompletionActions {
+    async fn create_chat_completion(
+        &self,
+        payload: &ChatCompletionBuilder,
+    ) -> Result<ChatCompletion, OpenApiError>;
+
+    async fn create_chat_completion_stream(
+        &self,
+        payload: &ChatCompletionBuilder,
+    ) -> Result<AsyncChatCompletionStream, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncChatCompletionActions for AsyncNetworking {
+    async fn create_chat_completion(
+        &self,
+        payload: &ChatCompletionBuilder,
+    ) -> Result<ChatCompletion, OpenApiError> {
+        if payload.is_stream() {
+            return Err(OpenApiError::RestrictedValue(
+                "Streamed chat completions must be created with create_chat_completion_stream"
+                    .into(),
+            ));
+        }
+        self.send_and_convert(
+            Method::POST,
+            String::from("chat/completions"),
+            Some(payload.request_body()?),
+            None,
+        )
+        .await
+    }
+
+    async fn create_chat_completion_stream(
+        &self,
+        payload: &ChatCompletionBuilder,
+    ) -> Result<AsyncChatCompletionStream, OpenApiError> {
+        let payload = payload.clone().with_stream(true);
+        let response = self
+            .send_streaming(
+                Method::POST,
+                String::from("chat/completions"),
+                Some(payload.request_body()?),
+            )
+            .await?;
+        Ok(AsyncChatCompletionStream::new(response))
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::Instrument;
+
+use crate::networking::config::NetworkingConfig;
+use crate::networking::logging;
+use crate::networking::transport::{
+    AsyncReqwestTransport, AsyncStreamingResponse, AsyncTransport, TransportRequest,
+    TransportResponse,
+};
+use crate::networking::{
+    AssistantsVersion, MultipartForm, RateLimiter, Reply, ResponseMetadata, RetryPolicy,
+};
+use crate::types::error::OpenApiError;
+use crate::types::moderation::Moderation;
+
+/// Async counterpart to `Networking`, dispatching every request through the async
+/// `reqwest::Client` so it can be driven from within an existing runtime (e.g. tokio).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AsyncNetworking {
+    #[serde(flatten)]
+    config: NetworkingConfig,
+    #[serde(skip, default = "default_transport")]
+    transport: Arc<dyn AsyncTransport>,
+    #[serde(skip)]
+    last_response: Arc<Mutex<Option<ResponseMetadata>>>,
+}
+
+fn default_transport() -> Arc<dyn AsyncTransport> {
+    Arc::new(AsyncReqwestTransport::default())
+}
+
+impl Default for AsyncNetworking {
+    fn default() -> Self {
+        Self::new(NetworkingConfig::default())
+    }
+}
+
+impl AsyncNetworking {
+    /* ---- Common Networking ---- */
+
+    pub(crate) fn new(config: NetworkingConfig) -> Self {
+        Self {
+            config,
+            transport: default_transport(),
+            last_response: Arc::default(),
+        }
+    }
+
+    /// Dispatches every request through `transport` instead of the default reqwest client.
+    pub fn with_transport<T: AsyncTransport + 'static>(self, transport: T) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            ..self
+        }
+    }
+
+    /// Sets the Assistants api version sent as `OpenAI-Beta`, by default `AssistantsVersion::V2`.
+    pub fn with_assistants_version(self, assistants_version: AssistantsVersion) -> Self {
+        Self {
+            config: self.config.with_assistants_version(assistants_version),
+            ..self
+        }
+    }
+
+    /// Retries transient failures according to `retry_policy`, by default no retries are made.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            config: self.config.with_retry_policy(retry_policy),
+            ..self
+        }
+    }
+
+    /// Returns the model configured as the default, e.g. from a profile.
+    pub fn get_default_model(&self) -> Option<&str> {
+        self.config.get_default_model()
+    }
+
+    /// Returns the metadata of the most recent response, including error responses. Clones of
+    /// this instance share the same record, so with concurrent calls the last to complete wins.
+    pub fn last_response_metadata(&self) -> Option<ResponseMetadata> {
+        self.last_response.lock().unwrap().clone()
+    }
+
+    /// Paces requests through `rate_limiter` before they are sent, see `RateLimiter`.
+    pub fn with_rate_limiter(self, rate_limiter: RateLimiter) -> Self {
+        Self {
+            config: self.config.with_rate_limiter(rate_limiter),
+            ..self
+        }
+    }
+
+    /// Emits request and response bodies as `tracing` trace events, disabled by default as bodies
+    /// may contain user content.
+    pub fn with_body_logging(self, log_bodies: bool) -> Self {
+        Self {
+            config: self.config.with_body_logging(log_bodies),
+            ..self
+        }
+    }
+
+    async fn send_request(
+        &self,
+        method: Method,
+        endpoint: String,
+        body: Option<Value>,
+        multipart_form: Option<MultipartForm>,
+    ) -> Result<Value, OpenApiError> {
+        match self
+            .dispatch(method, endpoint, body, multipart_form, false)
+            .await?
+        {
+            Reply::Json(value) => Ok(value),
+            Reply::Stream(_) => unreachable!("only streamed requests return a stream"),
+        }
+    }
+
+    /// Sends a request whose response body is read incrementally, e.g. a streamed chat
+    /// completion. Failed attempts are retried as usual, but only until the response has started.
+    pub async fn send_streaming(
+        &self,
+        method: Method,
+        endpoint: String,
+        body: Option<Value>,
+    ) -> Result<AsyncStreamingResponse, OpenApiError> {
+        match self.dispatch(method, endpoint, body, None, true).await? {
+            Reply::Stream(response) => Ok(response),
+            Reply::Json(_) => unreachable!("streamed requests always return a stream"),
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        method: Method,
+        endpoint: String,
+        body: Option<Value>,
+        multipart_form: Option<MultipartForm>,
+        stream: bool,
+    ) -> Result<Reply<AsyncStreamingResponse>, OpenApiError> {
+        let span = logging::request_span(&method, &endpoint);
+        async move {
+            let (url, body) = self.config.prepare_request(endpoint.clone(), body)?;
+            let mut attempt: u32 = 1;
+            loop {
+                let (permit, delay) = self.config.throttle(&endpoint, &body);
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                let lease = self.config.lease_key();
+                let request = self.config.build_request(
+                    lease.as_ref(),
+                    method.clone(),
+                    url.clone(),
+                    &body,
+                    &multipart_form,
+                );
+                let result = match request {
+                    Ok(request) => self.send_attempt(attempt, request, stream).await,
+                    Err(err) => Err(err),
+                };
+                match self
+                    .config
+                    .settle_attempt(attempt, &method, lease, permit, &result)
+                {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return result,
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// A single attempt of a request, streamed responses are only read in full on failure.
+    async fn send_attempt(
+        &self,
+        attempt: u32,
+        request: TransportRequest,
+        stream: bool,
+    ) -> Result<Reply<AsyncStreamingResponse>, OpenApiError> {
+        let started = Instant::now();
+        let res = match stream {
+            true => {
+                let mut res = self.transport.send_streaming(request).await?;
+                if res.status.is_success() {
+                    let metadata =
+                        self.config
+                            .stream_started(attempt, res.status, &res.headers, started);
+                    *self.last_response.lock().unwrap() = Some(metadata);
+                    return Ok(Reply::Stream(res));
+                }
+                // Errors are sent as a regular JSON body, so read it in full
+                let mut body = Vec::new();
+                while let Some(chunk) = res.body.next_chunk().await? {
+                    body.extend_from_slice(&chunk);
+                }
+                TransportResponse::new(res.status, res.headers, body)
+            }
+            false => self.transport.send(request).await?,
+        };
+        let (metadata, result) = self.config.read_response(attempt, &res, started);
+        *self.last_response.lock().unwrap() = Some(metadata);
+        result.map(Reply::Json)
+    }
+
+    pub async fn send_and_convert<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: String,
+        body: Option<Value>,
+        multipart_form: Option<MultipartForm>,
+    ) -> Result<T, OpenApiError> {
+        let val = self
+            .send_request(method, endpoint, body, multipart_form)
+            .await?;
+        serde_json::from_value::<T>(val).map_err(OpenApiError::from)
+    }
+
+    /* ---- Moderation ----
+     * No builder for create_moderation, uses client method
+     */
+
+    pub async fn create_moderation(
+        &self,
+        payload: HashMap<String, String>,
+    ) -> Result<Moderation, OpenApiError> {
+        self.send_and_convert(
+            Method::POST,
+            String::from("moderations"),
+            Some(serde_json::to_value(payload)?),
+            None,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networking::fixtures;
+    use crate::networking::transport::MockTransport;
+
+    fn networking(mock: &MockTransport, retry_policy: RetryPolicy) -> AsyncNetworking {
+        AsyncNetworking::new(fixtures::config(retry_policy)).with_transport(mock.clone())
+    }
+
+    #[tokio::test]
+    async fn test_error_response_decoded() {
+        let mock = fixtures::not_found_mock();
+        fixtures::assert_not_found(
+            networking(&mock, RetryPolicy::none())
+                .send_and_convert(Method::GET, "models/gpt-5".into(), None, None)
+                .await,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_key_pool_rotates_after_rate_limit() {
+        let (pool, mock) = fixtures::rate_limited_pool();
+        let config = NetworkingConfig::default().with_key_pool(pool.clone());
+        let networking = AsyncNetworking::new(config).with_transport(mock.clone());
+        let _: Value = networking
+            .send_and_convert(Method::GET, "models".into(), None, None)
+            .await
+            .unwrap();
+        fixtures::assert_key_rotated(&pool, &mock);
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_retried() {
+        let mock = fixtures::transient_failure_mock();
+        let networking = networking(&mock, fixtures::single_retry());
+        let value: Value = networking
+            .send_and_convert(Method::GET, "models".into(), None, None)
+            .await
+            .unwrap();
+        let metadata = networking.last_response_metadata().unwrap();
+        fixtures::assert_retried(&mock, value, metadata);
+    }
+
+    #[tokio::test]
+    async fn test_list_params_sent_as_query() {
+        use crate::networking::AsyncMessageActions;
+
+        let (mock, params) = fixtures::messages_page();
+        let messages = networking(&mock, RetryPolicy::none())
+            .list_messages("thread_abc".into(), Some(&params))
+            .await
+            .unwrap();
+        fixtures::assert_listed_with_query(&mock, messages);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_streamed() {
+        use crate::types::chat::ChatCompletionBuilder;
+
+        let mock = fixtures::chat_stream_mock();
+        let mut stream = ChatCompletionBuilder::new("gpt-4", Vec::new())
+            .with_include_usage(true)
+            .build_stream_async(&networking(&mock, RetryPolicy::none()))
+            .await
+            .unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunks.push(chunk.unwrap());
+        }
+        fixtures::assert_streamed(&mock, &chunks);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::common::{ApiList, DeletionStatus, ListParams};
+use crate::error::OpenApiError;
+use crate::file::{File, FileTypes};
+use crate::networking::{AsyncNetworking, MultipartForm};
+use crate::strip_edges;
+use async_trait::async_trait;
+use reqwest::Method;
+use std::path::PathBuf;
+
+#[async_trait]
+pub trait AsyncFileActions {
+    async fn upload_file(&self, file: PathBuf, purpose: FileTypes) -> Result<File, OpenApiError>;
+    async fn list_files(&self, params: Option<&ListParams>) -> Result<ApiList<File>, OpenApiError>;
+    async fn retrieve_file(&self, file_id: String) -> Result<File, OpenApiError>;
+    async fn delete_file(&self, file_id: String) -> Result<DeletionStatus, OpenApiError>;
+    async fn retrieve_file_content(&self, file_id: String) -> Result<String, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncFileActions for AsyncNetworking {
+    async fn upload_file(&self, file: PathBuf, purpose: FileTypes) -> Result<File, OpenApiError> {
+        let form = MultipartForm::new()
+            .text("purpose", strip_edges!(serde_json::to_string(&purpose)?))
+            .file("file", file);
+
+        self.send_and_convert(Method::POST, String::from("files"), None, Some(form))
+            .await
+    }
+
+    async fn list_files(&self, params: Option<&ListParams>) -> Result<ApiList<File>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(String::from("files"), params)?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_file(&self, file_id: String) -> Result<File, OpenApiError> {
+        self.send_and_convert(Method::GET, format!("files/{}", file_id), None, None)
+            .await
+    }
+
+    async fn delete_file(&self, file_id: String) -> Result<DeletionStatus, OpenApiError> {
+        self.send_and_convert(Method::DELETE, format!("files/{}", file_id), None, None)
+            .await
+    }
+
+    //TODO Check the return type correct
+    async fn retrieve_file_content(&self, file_id: String) -> Result<String, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!("files/{}/content", file_id),
+            None,
+            None,
+        )
+        .await
+    }
+}
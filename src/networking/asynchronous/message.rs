@@ -0,0 +1,124 @@
+use crate::common::{ApiList, ListParams};
+use crate::error::OpenApiError;
+use crate::message::{Message, MessageBuilder, MessageFile};
+use crate::networking::AsyncNetworking;
+use async_trait::async_trait;
+use reqwest::Method;
+
+#[async_trait]
+pub trait AsyncMessageActions {
+    async fn create_message(
+        &self,
+        payload: &MessageBuilder,
+        thread_id: &str,
+    ) -> Result<Message, OpenApiError>;
+    async fn list_messages(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Message>, OpenApiError>;
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
+    async fn list_message_files(
+        &self,
+        thread_id: String,
+        message_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<MessageFile>, OpenApiError>;
+    async fn retrieve_message(
+        &self,
+        thread_id: String,
+        message_id: String,
+    ) -> Result<Message, OpenApiError>;
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
+    async fn retrieve_message_file(
+        &self,
+        thread_id: String,
+        message_id: String,
+        file_id: String,
+    ) -> Result<MessageFile, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncMessageActions for AsyncNetworking {
+    async fn create_message(
+        &self,
+        payload: &MessageBuilder,
+        thread_id: &str,
+    ) -> Result<Message, OpenApiError> {
+        self.send_and_convert(
+            Method::POST,
+            format!("threads/{}/messages", thread_id),
+            Some(serde_json::to_value(payload)?),
+            None,
+        )
+        .await
+    }
+
+    async fn list_messages(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Message>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(format!("threads/{}/messages", thread_id), params)?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn list_message_files(
+        &self,
+        thread_id: String,
+        message_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<MessageFile>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(
+                format!("threads/{0}/messages/{1}/files", thread_id, message_id),
+                params,
+            )?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_message(
+        &self,
+        thread_id: String,
+        message_id: String,
+    ) -> Result<Message, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!("threads/{0}/messages/{1}", thread_id, message_id),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_message_file(
+        &self,
+        thread_id: String,
+        message_id: String,
+        file_id: String,
+    ) -> Result<MessageFile, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!(
+                "threads/{0}/messages/{1}/files/{2}",
+                thread_id, message_id, file_id
+            ),
+            None,
+            None,
+        )
+        .await
+    }
+}
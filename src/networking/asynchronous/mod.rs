@@ -0,0 +1,15 @@
+mod assistant;
+mod chat;
+mod core;
+mod file;
+mod message;
+mod model;
+mod run;
+mod thread;
+
+pub use self::core::AsyncNetworking;
+pub use self::{
+    assistant::AsyncAssistantActions, chat::AsyncChatCompletionActions, file::AsyncFileActions,
+    message::AsyncMessageActions, model::AsyncModelActions, run::AsyncRunActions,
+    thread::AsyncThreadActions,
+};
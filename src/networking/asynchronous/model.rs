@@ -0,0 +1,25 @@
+use crate::common::ApiList;
+use crate::error::OpenApiError;
+use crate::model::Model;
+use crate::networking::AsyncNetworking;
+use async_trait::async_trait;
+use reqwest::Method;
+
+#[async_trait]
+pub trait AsyncModelActions {
+    async fn list_models(&self) -> Result<ApiList<Model>, OpenApiError>;
+    async fn load_model(&self, model_id: String) -> Result<Model, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncModelActions for AsyncNetworking {
+    async fn list_models(&self) -> Result<ApiList<Model>, OpenApiError> {
+        self.send_and_convert(Method::GET, String::from("models"), None, None)
+            .await
+    }
+
+    async fn load_model(&self, model_id: String) -> Result<Model, OpenApiError> {
+        self.send_and_convert(Method::GET, format!("models/{}", model_id), None, None)
+            .await
+    }
+}
@@ -0,0 +1,123 @@
+use crate::common::{ApiList, ListParams};
+use crate::error::OpenApiError;
+use crate::networking::AsyncNetworking;
+use crate::run::{Run, RunStep};
+use async_trait::async_trait;
+use reqwest::Method;
+use serde::Serialize;
+
+#[async_trait]
+pub trait AsyncRunActions {
+    async fn create_run<S: Serialize + Sync>(
+        &self,
+        payload: &S,
+        thread_id: &Option<String>,
+    ) -> Result<Run, OpenApiError>;
+    async fn retrieve_run(&self, thread_id: String, run_id: String) -> Result<Run, OpenApiError>;
+    async fn retrieve_run_step(
+        &self,
+        thread_id: String,
+        run_id: String,
+        step_id: String,
+    ) -> Result<RunStep, OpenApiError>;
+    async fn list_runs(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Run>, OpenApiError>;
+    async fn list_run_steps(
+        &self,
+        thread_id: String,
+        run_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<RunStep>, OpenApiError>;
+    async fn cancel_run(&self, thread_id: String, run_id: String) -> Result<Run, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncRunActions for AsyncNetworking {
+    async fn create_run<S: Serialize + Sync>(
+        &self,
+        payload: &S,
+        thread_id: &Option<String>,
+    ) -> Result<Run, OpenApiError> {
+        let endpoint: String = match thread_id {
+            Some(tid) => format!("threads/{}/runs", tid),
+            None => String::from("threads/runs"),
+        };
+        self.send_and_convert(
+            Method::POST,
+            endpoint,
+            Some(serde_json::to_value(payload)?),
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_run(&self, thread_id: String, run_id: String) -> Result<Run, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!("threads/{0}/runs/{1}", thread_id, run_id),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_run_step(
+        &self,
+        thread_id: String,
+        run_id: String,
+        step_id: String,
+    ) -> Result<RunStep, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            format!("threads/{0}/runs/{1}/steps/{2}", thread_id, run_id, step_id),
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn list_runs(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Run>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(format!("threads/{0}/runs", thread_id), params)?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn list_run_steps(
+        &self,
+        thread_id: String,
+        run_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<RunStep>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(
+                format!("threads/{0}/runs/{1}/steps", thread_id, run_id),
+                params,
+            )?,
+            None,
+            None,
+        )
+        .await
+    }
+
+    async fn cancel_run(&self, thread_id: String, run_id: String) -> Result<Run, OpenApiError> {
+        self.send_and_convert(
+            Method::POST,
+            format!("threads/{0}/runs/{1}/cancel", thread_id, run_id),
+            None,
+            None,
+        )
+        .await
+    }
+}
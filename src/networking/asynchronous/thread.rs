@@ -0,0 +1,36 @@
+use crate::common::DeletionStatus;
+use crate::error::OpenApiError;
+use crate::networking::AsyncNetworking;
+use crate::thread::{Thread, ThreadBuilder};
+use async_trait::async_trait;
+use reqwest::Method;
+
+#[async_trait]
+pub trait AsyncThreadActions {
+    async fn create_thread(&self, payload: &ThreadBuilder) -> Result<Thread, OpenApiError>;
+    async fn retrieve_thread(&self, thread_id: String) -> Result<Thread, OpenApiError>;
+    async fn delete_thread(&self, thread_id: String) -> Result<DeletionStatus, OpenApiError>;
+}
+
+#[async_trait]
+impl AsyncThreadActions for AsyncNetworking {
+    async fn create_thread(&self, payload: &ThreadBuilder) -> Result<Thread, OpenApiError> {
+        self.send_and_convert(
+            Method::POST,
+            String::from("threads"),
+            Some(serde_json::to_value(payload)?),
+            None,
+        )
+        .await
+    }
+
+    async fn retrieve_thread(&self, thread_id: String) -> Result<Thread, OpenApiError> {
+        self.send_and_convert(Method::GET, format!("threads/{}", thread_id), None, None)
+            .await
+    }
+
+    async fn delete_thread(&self, thread_id: String) -> Result<DeletionStatus, OpenApiError> {
+        self.send_and_convert(Method::DELETE, format!("threads/{}", thread_id), None, None)
+            .await
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::common::Identifiable;
+use crate::types::error::OpenApiError;
+
+/// Endpoints that Azure scopes to a deployment, i.e. `openai/deployments/{deployment}/{endpoint}`.
+/// Every other endpoint is served from `openai/{endpoint}` with the deployment given as the model.
+const DEPLOYMENT_ENDPOINTS: [&str; 7] = [
+    "chat/completions",
+    "completions",
+    "embeddings",
+    "audio/speech",
+    "audio/transcriptions",
+    "audio/translations",
+    "images/generations",
+];
+
+/// `AzureConfig` switches the client into Azure OpenAI mode, where requests are authenticated
+/// with an `api-key` header, carry an `api-version` query and are routed to deployments rather
+/// than models.
+///
+/// # Fields
+///
+/// * `api_version: String` - The Azure OpenAI api version sent with every request, e.g. `2024-02-15-preview`.
+/// * `deployments: HashMap<String, String>` - A map of model identifiers to deployment names. Models without an entry are assumed to share the name of their deployment.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AzureConfig {
+    api_version: String,
+    deployments: HashMap<String, String>,
+}
+
+impl AzureConfig {
+    pub fn new<V: Into<String>>(api_version: V) -> Self {
+        Self {
+            api_version: api_version.into(),
+            ..AzureConfig::default()
+        }
+    }
+
+    pub fn with_deployments(mut self, deployments: HashMap<String, String>) -> Self {
+        self.deployments = deployments;
+        self
+    }
+
+    pub fn add_deployment<M: Identifiable, D: Into<String>>(
+        mut self,
+        model: M,
+        deployment: D,
+    ) -> Self {
+        self.deployments
+            .insert(model.get_identifier(), deployment.into());
+        self
+    }
+
+    /// Returns the deployment serving `model`, falling back to the model name itself.
+    pub fn deployment_for<'a>(&'a self, model: &'a str) -> &'a str {
+        self.deployments
+            .get(model)
+            .map(String::as_str)
+            .unwrap_or(model)
+    }
+
+    /// Rewrites an OpenAI endpoint and payload into their Azure equivalents. Deployment scoped
+    /// endpoints have the deployment inserted into the path, any other payload carrying a
+    /// `model` has it replaced with the deployment name.
+    pub(crate) fn rewrite_request(
+        &self,
+        base_url: &Url,
+        endpoint: String,
+        mut body: Option<Value>,
+    ) -> Result<(Url, Option<Value>), OpenApiError> {
+        let endpoint = endpoint.trim_start_matches('/');
+        let model = body
+            .as_ref()
+            .and_then(|b| b.get("model"))
+            .and_then(Value::as_str)
+            .map(|m| self.deployment_for(m).to_string());
+
+        let path = if DEPLOYMENT_ENDPOINTS.contains(&endpoint) {
+            let deployment = model.ok_or_else(|| {
+                OpenApiError::ClientError(format!(
+                    "Azure endpoint {} requires a model to resolve the deployment",
+                    endpoint
+                ))
+            })?;
+            format!("openai/deployments/{}/{}", deployment, endpoint)
+        } else {
+            if let (Some(deployment), Some(Value::Object(payload))) = (model, body.as_mut()) {
+                payload.insert(String::from("model"), Value::String(deployment));
+            }
+            format!("openai/{}", endpoint)
+        };
+
+        let mut url = base_url.join(&path)?;
+        url.query_pairs_mut()
+            .append_pair("api-version", &self.api_version);
+        Ok((url, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn azure() -> (AzureConfig, Url) {
+        let azure = AzureConfig::new("2024-02-15-preview").add_deployment("gpt-4", "gpt4-prod");
+        let base_url = Url::parse("https://tenant.openai.azure.com/").unwrap();
+        (azure, base_url)
+    }
+
+    #[test]
+    fn test_chat_completion_routes_to_deployment() {
+        let (azure, base_url) = azure();
+        let body = json!({"model": "gpt-4", "messages": []});
+        let (url, _) = azure
+            .rewrite_request(&base_url, String::from("chat/completions"), Some(body))
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://tenant.openai.azure.com/openai/deployments/gpt4-prod/chat/completions?api-version=2024-02-15-preview"
+        );
+    }
+
+    #[test]
+    fn test_assistant_model_replaced_with_deployment() {
+        let (azure, base_url) = azure();
+        let body = json!({"model": "gpt-4"});
+        let (url, body) = azure
+            .rewrite_request(&base_url, String::from("assistants"), Some(body))
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://tenant.openai.azure.com/openai/assistants?api-version=2024-02-15-preview"
+        );
+        assert_eq!(body.unwrap()["model"], "gpt4-prod");
+    }
+
+    #[test]
+    fn test_unmapped_model_used_as_deployment() {
+        let (azure, _) = azure();
+        assert_eq!(azure.deployment_for("gpt-35-turbo"), "gpt-35-turbo");
+    }
+
+    #[test]
+    fn test_deployment_endpoint_without_model() {
+        let (azure, base_url) = azure();
+        let result = azure.rewrite_request(&base_url, String::from("chat/completions"), None);
+        assert!(result.is_err());
+    }
+}
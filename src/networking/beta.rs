@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// The version of the Assistants api requested through the `OpenAI-Beta` header. The v1 api has
+/// been retired, so `V2` is the default; `V1` remains for gateways still serving the old shape.
+///
+/// # Examples
+/// ```ignore
+/// let client = OpenAIClient::new(apikey, None)?.with_assistants_version(AssistantsVersion::V1);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssistantsVersion {
+    V1,
+    #[default]
+    V2,
+}
+
+impl AssistantsVersion {
+    /// Returns the value of the `OpenAI-Beta` header, e.g. `assistants=v2`.
+    pub fn header_value(&self) -> &'static str {
+        match self {
+            AssistantsVersion::V1 => "assistants=v1",
+            AssistantsVersion::V2 => "assistants=v2",
+        }
+    }
+}
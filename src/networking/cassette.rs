@@ -0,0 +1,384 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::networking::transport::{RequestBody, TransportRequest, TransportResponse};
+use crate::types::error::OpenApiError;
+
+#[cfg(feature = "async")]
+use crate::networking::transport::{AsyncReqwestTransport, AsyncTransport};
+#[cfg(feature = "blocking")]
+use crate::networking::transport::{ReqwestTransport, Transport};
+
+/// Placeholder written to a cassette in place of secrets.
+const REDACTED: &str = "<redacted>";
+
+/// Response headers that are never written to a cassette.
+const SKIPPED_HEADERS: [&str; 2] = ["set-cookie", "openai-organization"];
+
+/// A recorded exchange, the request is stored without headers so no credentials are kept.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+struct RecordedRequest {
+    method: String,
+    endpoint: String,
+    body: Option<Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Value,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record,
+    Replay { used: Vec<bool> },
+}
+
+#[derive(Debug)]
+struct CassetteState {
+    path: PathBuf,
+    mode: Mode,
+    cassette: CassetteFile,
+}
+
+impl RecordedRequest {
+    /// Reduces a request to what is matched on replay: the method, the endpoint relative to the
+    /// host and the body, with JSON objects compared irrespective of key order.
+    fn from_request(request: &TransportRequest) -> Self {
+        let endpoint = match request.url.query() {
+            Some(query) => format!("{}?{}", request.url.path(), query),
+            None => request.url.path().to_string(),
+        };
+        let body = match &request.body {
+            Some(RequestBody::Json(value)) => Some(value.clone()),
+            Some(RequestBody::Multipart(form)) => Some(form.describe()),
+            None => None,
+        };
+        Self {
+            method: request.method.to_string(),
+            endpoint,
+            body,
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn from_response(response: &TransportResponse) -> Self {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, value)| {
+                !value.is_sensitive() && !SKIPPED_HEADERS.contains(&name.as_str())
+            })
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        // Bodies are kept as JSON where possible so cassettes remain readable and editable
+        let body = serde_json::from_slice(&response.body).unwrap_or_else(|_| {
+            Value::String(String::from_utf8_lossy(&response.body).into_owned())
+        });
+        Self {
+            status: response.status.as_u16(),
+            headers,
+            body,
+        }
+    }
+
+    fn to_response(&self) -> Result<TransportResponse, OpenApiError> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|e| OpenApiError::ClientError(format!("Invalid cassette status: {}", e)))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        let body = match &self.body {
+            Value::String(text) => text.clone().into_bytes(),
+            value => serde_json::to_vec(value)?,
+        };
+        Ok(TransportResponse::new(status, headers, body))
+    }
+}
+
+/// Returns the key sent with `request`, so it can be scrubbed from anything recorded.
+fn request_secret(request: &TransportRequest) -> Option<String> {
+    let authorization = request
+        .headers
+        .get("authorization")
+        .or_else(|| request.headers.get("api-key"))?
+        .to_str()
+        .ok()?;
+    let secret = authorization.trim_start_matches("Bearer ").trim();
+    (!secret.is_empty()).then(|| secret.to_string())
+}
+
+/// Replaces every occurrence of `secret` within the strings of `value`.
+fn scrub(value: &mut Value, secret: &str) {
+    match value {
+        Value::String(text) if text.contains(secret) => *text = text.replace(secret, REDACTED),
+        Value::Array(values) => values.iter_mut().for_each(|v| scrub(v, secret)),
+        Value::Object(map) => map.values_mut().for_each(|v| scrub(v, secret)),
+        _ => {}
+    }
+}
+
+impl CassetteState {
+    fn record(path: PathBuf) -> Self {
+        Self {
+            path,
+            mode: Mode::Record,
+            cassette: CassetteFile::default(),
+        }
+    }
+
+    fn replay(path: PathBuf) -> Result<Self, OpenApiError> {
+        let cassette: CassetteFile = serde_json::from_slice(&fs::read(&path)?)?;
+        let used = vec![false; cassette.interactions.len()];
+        Ok(Self {
+            path,
+            mode: Mode::Replay { used },
+            cassette,
+        })
+    }
+
+    fn is_recording(&self) -> bool {
+        matches!(self.mode, Mode::Record)
+    }
+
+    /// Serves the first unused interaction matching `request`, so repeated requests such as run
+    /// polling are answered in the order they were recorded.
+    fn replay_response(
+        &mut self,
+        request: &TransportRequest,
+    ) -> Result<TransportResponse, OpenApiError> {
+        let recorded = RecordedRequest::from_request(request);
+        let used = match &mut self.mode {
+            Mode::Replay { used } => used,
+            Mode::Record => unreachable!("replay_response called while recording"),
+        };
+        let index = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .position(|(i, interaction)| !used[i] && interaction.request == recorded)
+            .ok_or_else(|| {
+                OpenApiError::ClientError(format!(
+                    "Cassette {} has no unused interaction for {} {}",
+                    self.path.display(),
+                    recorded.method,
+                    recorded.endpoint
+                ))
+            })?;
+        used[index] = true;
+        self.cassette.interactions[index].response.to_response()
+    }
+
+    /// Appends the exchange to the cassette and rewrites the file, scrubbing the api key.
+    fn record_interaction(
+        &mut self,
+        request: &TransportRequest,
+        response: &TransportResponse,
+    ) -> Result<(), OpenApiError> {
+        let mut interaction = serde_json::to_value(Interaction {
+            request: RecordedRequest::from_request(request),
+            response: RecordedResponse::from_response(response),
+        })?;
+        if let Some(secret) = request_secret(request) {
+            scrub(&mut interaction, &secret);
+        }
+        self.cassette
+            .interactions
+            .push(serde_json::from_value(interaction)?);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&self.cassette)?)?;
+        Ok(())
+    }
+}
+
+/// VCR style transport for deterministic tests of the blocking client.
+///
+/// In record mode every exchange is forwarded to an inner transport and written to a JSON cassette
+/// file, with the api key scrubbed. In replay mode responses are served from the cassette and the
+/// network is never touched. Requests are matched by method, endpoint and body.
+///
+/// # Examples
+/// ```ignore
+/// // Record once against the live api, then commit the cassette
+/// let client = OpenAIClient::new_with_env(None)?
+///     .with_transport(Cassette::record("tests/cassettes/run.json"));
+/// // Replay in CI, offline
+/// let client = OpenAIClient::new("sk-test", None)?
+///     .with_transport(Cassette::replay("tests/cassettes/run.json")?);
+/// ```
+#[cfg(feature = "blocking")]
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    state: Arc<Mutex<CassetteState>>,
+    inner: Option<Arc<dyn Transport>>,
+}
+
+#[cfg(feature = "blocking")]
+impl Cassette {
+    /// Records to `path` using the default reqwest transport, replacing any existing cassette.
+    pub fn record<P: AsRef<Path>>(path: P) -> Self {
+        Cassette::record_with(path, ReqwestTransport::default())
+    }
+
+    /// Records to `path` the exchanges made through `inner`.
+    pub fn record_with<P: AsRef<Path>, T: Transport + 'static>(path: P, inner: T) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CassetteState::record(path.as_ref().into()))),
+            inner: Some(Arc::new(inner)),
+        }
+    }
+
+    /// Replays the cassette at `path`.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self, OpenApiError> {
+        Ok(Self {
+            state: Arc::new(Mutex::new(CassetteState::replay(path.as_ref().into())?)),
+            inner: None,
+        })
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Transport for Cassette {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        let recording = self.state.lock().unwrap().is_recording();
+        let inner = match &self.inner {
+            Some(inner) if recording => inner,
+            _ => return self.state.lock().unwrap().replay_response(&request),
+        };
+        let response = inner.send(request.clone())?;
+        self.state
+            .lock()
+            .unwrap()
+            .record_interaction(&request, &response)?;
+        Ok(response)
+    }
+}
+
+/// Async counterpart to `Cassette`, for use with the `AsyncOpenAIClient`.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone)]
+pub struct AsyncCassette {
+    state: Arc<Mutex<CassetteState>>,
+    inner: Option<Arc<dyn AsyncTransport>>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncCassette {
+    /// Records to `path` using the default reqwest transport, replacing any existing cassette.
+    pub fn record<P: AsRef<Path>>(path: P) -> Self {
+        AsyncCassette::record_with(path, AsyncReqwestTransport::default())
+    }
+
+    /// Records to `path` the exchanges made through `inner`.
+    pub fn record_with<P: AsRef<Path>, T: AsyncTransport + 'static>(path: P, inner: T) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CassetteState::record(path.as_ref().into()))),
+            inner: Some(Arc::new(inner)),
+        }
+    }
+
+    /// Replays the cassette at `path`.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self, OpenApiError> {
+        Ok(Self {
+            state: Arc::new(Mutex::new(CassetteState::replay(path.as_ref().into())?)),
+            inner: None,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncTransport for AsyncCassette {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        let recording = self.state.lock().unwrap().is_recording();
+        let inner = match &self.inner {
+            Some(inner) if recording => inner,
+            _ => return self.state.lock().unwrap().replay_response(&request),
+        };
+        let response = inner.send(request.clone()).await?;
+        self.state
+            .lock()
+            .unwrap()
+            .record_interaction(&request, &response)?;
+        Ok(response)
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+    use crate::networking::transport::MockTransport;
+    use reqwest::{Method, Url};
+    use serde_json::json;
+
+    fn request(body: Value) -> TransportRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer sk-secret".parse().unwrap());
+        TransportRequest {
+            method: Method::POST,
+            url: Url::parse("https://api.openai.com/v1/threads").unwrap(),
+            headers,
+            body: Some(RequestBody::Json(body)),
+        }
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = std::env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+        let mock = MockTransport::new()
+            .with_json(
+                Method::POST,
+                "threads",
+                StatusCode::OK,
+                &json!({"id": "thread_1", "echo": "sk-secret"}),
+            )
+            .unwrap();
+        let cassette = Cassette::record_with(&path, mock);
+        cassette
+            .send(request(json!({"a": 1, "b": [1, 2]})))
+            .unwrap();
+
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert!(!recorded.contains("sk-secret"));
+
+        let replay = Cassette::replay(&path).unwrap();
+        // Matched irrespective of key order, and each interaction is served once
+        let response = replay.send(request(json!({"b": [1, 2], "a": 1}))).unwrap();
+        let body: Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["id"], "thread_1");
+        assert!(replay.send(request(json!({"a": 1, "b": [1, 2]}))).is_err());
+        assert!(replay.send(request(json!({"a": 2}))).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use crate::chat::{ChatCompletion, ChatCompletionBuilder};
 use crate::error::OpenApiError;
-use crate::networking::Networking;
+use crate::networking::{ChatCompletionStream, Networking};
 use reqwest::Method;
 
 pub trait ChatCompletionActions {
@@ -8,6 +8,11 @@ pub trait ChatCompletionActions {
         &self,
         payload: &ChatCompletionBuilder,
     ) -> Result<ChatCompletion, OpenApiError>;
+
+    fn create_chat_completion_stream(
+        &self,
+        payload: &ChatCompletionBuilder,
+    ) -> Result<ChatCompletionStream, OpenApiError>;
 }
 
 impl ChatCompletionActions for Networking {
@@ -15,11 +20,30 @@ impl ChatCompletionActions for Networking {
         &self,
         payload: &ChatCompletionBuilder,
     ) -> Result<ChatCompletion, OpenApiError> {
+        if payload.is_stream() {
+            return Err(OpenApiError::RestrictedValue(
+                "Streamed chat completions must be created with create_chat_completion_stream"
+                    .into(),
+            ));
+        }
         self.send_and_convert(
             Method::POST,
             String::from("chat/completions"),
-            Some(serde_json::to_value(payload)?),
+            Some(payload.request_body()?),
             None,
         )
     }
+
+    fn create_chat_completion_stream(
+        &self,
+        payload: &ChatCompletionBuilder,
+    ) -> Result<ChatCompletionStream, OpenApiError> {
+        let payload = payload.clone().with_stream(true);
+        let response = self.send_streaming(
+            Method::POST,
+            String::from("chat/completions"),
+            Some(payload.request_body()?),
+        )?;
+        Ok(ChatCompletionStream::new(response))
+    }
 }
@@ -0,0 +1,441 @@
+use std::env;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::credentials::ApiKey;
+use crate::networking::key_pool::KeyLease;
+use crate::networking::logging;
+use crate::networking::rate_limit::Permit;
+use crate::networking::transport::{RequestBody, TransportRequest, TransportResponse};
+use crate::networking::{
+    AssistantsVersion, AzureConfig, KeyPool, MultipartForm, RateLimiter, ResponseMetadata,
+    RetryPolicy,
+};
+use crate::types::error::OpenApiError;
+
+/// Base url used when neither an explicit base url nor `OPENAI_BASE_URL` is supplied.
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1/";
+
+/// The body of a successful attempt, parsed as JSON unless the request is streamed.
+pub(crate) enum Reply<S> {
+    Json(Value),
+    Stream(S),
+}
+
+impl<S> Reply<S> {
+    /// Returns the JSON body used to account for token usage. Streamed responses report no
+    /// usage up front, so they are charged as estimated.
+    fn json(&self) -> &Value {
+        match self {
+            Reply::Json(value) => value,
+            Reply::Stream(_) => &Value::Null,
+        }
+    }
+}
+
+/// Settings shared by the blocking `Networking` and the async `AsyncNetworking`, responsible for
+/// producing the headers and urls attached to every request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct NetworkingConfig {
+    #[serde(skip)]
+    apikey: ApiKey,
+    organization_id: Option<String>,
+    base_url: Url,
+    azure: Option<AzureConfig>,
+    assistants_version: AssistantsVersion,
+    retry_policy: RetryPolicy,
+    log_bodies: bool,
+    extra_headers: Vec<(String, String)>,
+    #[serde(skip)]
+    rate_limiter: Option<RateLimiter>,
+    #[serde(skip)]
+    key_pool: Option<KeyPool>,
+    default_model: Option<String>,
+}
+
+impl Default for NetworkingConfig {
+    fn default() -> Self {
+        Self {
+            apikey: ApiKey::default(),
+            organization_id: None,
+            base_url: Url::parse(DEFAULT_BASE_URL).unwrap(),
+            azure: None,
+            assistants_version: AssistantsVersion::default(),
+            retry_policy: RetryPolicy::none(),
+            log_bodies: false,
+            extra_headers: Vec::new(),
+            rate_limiter: None,
+            key_pool: None,
+            default_model: None,
+        }
+    }
+}
+
+impl NetworkingConfig {
+    /// Fails with `OpenApiError::ClientError` when the key or organization id cannot be sent as a
+    /// header, as both may come from a config file or a key command.
+    pub fn new<K: Into<ApiKey>>(
+        apikey: K,
+        organization_id: Option<String>,
+        base_url: Url,
+    ) -> Result<Self, OpenApiError> {
+        let config = Self {
+            apikey: apikey.into(),
+            organization_id,
+            base_url,
+            azure: None,
+            assistants_version: AssistantsVersion::default(),
+            retry_policy: RetryPolicy::none(),
+            log_bodies: false,
+            extra_headers: Vec::new(),
+            rate_limiter: None,
+            key_pool: None,
+            default_model: None,
+        };
+        config.construct_headers(None)?;
+        Ok(config)
+    }
+
+    /// Switches to Azure OpenAI mode, see `AzureConfig`. The base url should be the Azure
+    /// resource endpoint, e.g. `https://{resource}.openai.azure.com/`.
+    pub fn with_azure(mut self, azure: AzureConfig) -> Self {
+        self.azure = Some(azure);
+        self
+    }
+
+    /// Sets the Assistants api version sent as `OpenAI-Beta`, by default `AssistantsVersion::V2`.
+    pub fn with_assistants_version(mut self, assistants_version: AssistantsVersion) -> Self {
+        self.assistants_version = assistants_version;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_body_logging(mut self, log_bodies: bool) -> Self {
+        self.log_bodies = log_bodies;
+        self
+    }
+
+    pub fn with_default_model(mut self, default_model: String) -> Self {
+        self.default_model = Some(default_model);
+        self
+    }
+
+    pub fn get_default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+
+    /// Distributes requests over the keys of `key_pool`, in place of the single api key.
+    pub fn with_key_pool(mut self, key_pool: KeyPool) -> Self {
+        self.key_pool = Some(key_pool);
+        self
+    }
+
+    /// Checks out a key from the pool for an attempt, or `None` when no pool is attached.
+    pub fn lease_key(&self) -> Option<KeyLease> {
+        self.key_pool.as_ref().and_then(KeyPool::lease)
+    }
+
+    /// Returns the delay before retrying `err` on attempt `attempt`. With a key pool a 429 or 401
+    /// is retried immediately while another key is available, otherwise the retry policy decides.
+    pub fn retry_delay(
+        &self,
+        attempt: u32,
+        method: &Method,
+        err: &OpenApiError,
+    ) -> Option<Duration> {
+        if let (Some(pool), OpenApiError::Api(api_err)) = (&self.key_pool, err) {
+            let status = api_err.get_status();
+            let rotatable =
+                status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::UNAUTHORIZED;
+            if rotatable && (attempt as usize) < pool.len() && pool.has_available_key() {
+                return Some(Duration::ZERO);
+            }
+        }
+        self.retry_policy.retry_delay(attempt, method, err)
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Reserves rate limiter capacity for an attempt at `endpoint`, counting tokens for chat
+    /// completions. Returns `None` when no limiter is attached.
+    pub fn acquire_permit(&self, endpoint: &str, body: &Option<Value>) -> Option<Permit> {
+        let chat_body = match endpoint.trim_start_matches('/') {
+            "chat/completions" => body.as_ref(),
+            _ => None,
+        };
+        self.rate_limiter
+            .as_ref()
+            .map(|limiter| limiter.acquire(chat_body))
+    }
+
+    /// Reserves rate limiter capacity for an attempt, returning the permit alongside the delay to
+    /// wait before sending it.
+    pub fn throttle(&self, endpoint: &str, body: &Option<Value>) -> (Option<Permit>, Duration) {
+        let permit = self.acquire_permit(endpoint, body);
+        let delay = permit.as_ref().map_or(Duration::ZERO, Permit::get_delay);
+        if !delay.is_zero() {
+            logging::log_throttle(delay);
+        }
+        (permit, delay)
+    }
+
+    /// Builds the request for a single attempt, the payload is cloned so the attempt can be
+    /// repeated.
+    pub fn build_request(
+        &self,
+        lease: Option<&KeyLease>,
+        method: Method,
+        url: Url,
+        body: &Option<Value>,
+        multipart_form: &Option<MultipartForm>,
+    ) -> Result<TransportRequest, OpenApiError> {
+        let body = match (body, multipart_form) {
+            (Some(b), _) => Some(RequestBody::Json(b.clone())),
+            (None, Some(mf)) => Some(RequestBody::Multipart(mf.clone())),
+            (None, None) => None,
+        };
+        logging::log_request_body(&body, self.log_bodies);
+        Ok(TransportRequest {
+            method,
+            url,
+            headers: self.construct_headers(lease)?,
+            body,
+        })
+    }
+
+    /// Records the head of a successful streamed response, whose body is left to the caller.
+    pub fn stream_started(
+        &self,
+        attempt: u32,
+        status: StatusCode,
+        headers: &HeaderMap,
+        started: Instant,
+    ) -> ResponseMetadata {
+        logging::record_response_head(attempt, status, headers, started.elapsed());
+        ResponseMetadata::from_headers(status, headers)
+    }
+
+    /// Records a response read in full, returning its metadata alongside the decoded body, or the
+    /// `OpenApiError::Api` of a non-success status.
+    pub fn read_response(
+        &self,
+        attempt: u32,
+        res: &TransportResponse,
+        started: Instant,
+    ) -> (ResponseMetadata, Result<Value, OpenApiError>) {
+        logging::record_response(attempt, res, started.elapsed(), self.log_bodies);
+        let metadata = ResponseMetadata::from_headers(res.status, &res.headers);
+        let result = match res.status.is_success() {
+            true => serde_json::from_slice(&res.body).map_err(OpenApiError::from),
+            false => Err(OpenApiError::from_response(
+                res.status,
+                &res.headers,
+                &res.body,
+            )),
+        };
+        (metadata, result)
+    }
+
+    /// Reports the outcome of an attempt to the key pool and rate limiter, then returns the delay
+    /// before retrying it, or `None` when the outcome is final.
+    pub fn settle_attempt<S>(
+        &self,
+        attempt: u32,
+        method: &Method,
+        lease: Option<KeyLease>,
+        permit: Option<Permit>,
+        result: &Result<Reply<S>, OpenApiError>,
+    ) -> Option<Duration> {
+        if let Some(lease) = &lease {
+            lease.complete(result.as_ref().map(Reply::json));
+        }
+        if let Some(permit) = &permit {
+            permit.settle(result.as_ref().ok().map(Reply::json));
+        }
+        let err = result.as_ref().err()?;
+        let delay = self.retry_delay(attempt, method, err)?;
+        logging::log_retry(attempt, delay, err);
+        Some(delay)
+    }
+
+    /// Headers sent with every request, e.g. `OpenAI-Project`, replacing any default header of the
+    /// same name. Fails when a name or value is not a valid header.
+    pub fn with_extra_headers(
+        mut self,
+        extra_headers: Vec<(String, String)>,
+    ) -> Result<Self, OpenApiError> {
+        self.extra_headers = extra_headers;
+        self.construct_headers(None)?;
+        Ok(self)
+    }
+
+    /// Parses a base url such as `http://localhost:8000/v1`, appending a trailing slash when
+    /// missing so that endpoints are joined onto the base path instead of replacing its last
+    /// segment.
+    pub fn parse_base_url<T: AsRef<str>>(base_url: T) -> Result<Url, OpenApiError> {
+        let mut base_url = Url::parse(base_url.as_ref())?;
+        if base_url.cannot_be_a_base() {
+            return Err(OpenApiError::ClientError(format!(
+                "Base url {} cannot be used as a base",
+                base_url
+            )));
+        }
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
+        Ok(base_url)
+    }
+
+    /// Resolves the base url from `OPENAI_BASE_URL`, falling back to the OpenAI api.
+    pub fn base_url_from_env() -> Result<Url, OpenApiError> {
+        match env::var("OPENAI_BASE_URL") {
+            Ok(base_url) if !base_url.trim().is_empty() => Self::parse_base_url(base_url.trim()),
+            _ => Self::parse_base_url(DEFAULT_BASE_URL),
+        }
+    }
+
+    /// Constructs the headers for an attempt, authenticating with the leased key when given.
+    pub fn construct_headers(&self, lease: Option<&KeyLease>) -> Result<HeaderMap, OpenApiError> {
+        let (apikey, organization_id) = match lease {
+            Some(lease) => (
+                &lease.credential().apikey,
+                &lease.credential().organization_id,
+            ),
+            None => (&self.apikey, &self.organization_id),
+        };
+        let mut headers = HeaderMap::new();
+        // The key is only ever exposed here, and marked sensitive so it is redacted from `Debug`
+        let (auth_header, auth_value) = match self.azure {
+            Some(_) => ("api-key", apikey.expose().to_string()),
+            None => ("Authorization", format!("Bearer {}", apikey.expose())),
+        };
+        let mut auth_value = HeaderValue::from_str(&auth_value)
+            .map_err(|_| OpenApiError::ClientError(String::from("Invalid api key")))?;
+        auth_value.set_sensitive(true);
+        headers.insert(auth_header, auth_value);
+        headers.insert(
+            "OpenAI-Beta",
+            HeaderValue::from_static(self.assistants_version.header_value()),
+        );
+        if let Some(org_id) = organization_id {
+            headers.insert(
+                "OpenAI-Organization",
+                header_value("OpenAI-Organization", org_id)?,
+            );
+        }
+        for (name, value) in &self.extra_headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| OpenApiError::ClientError(format!("Invalid header name: {}", name)))?;
+            headers.insert(header_name, header_value(name, value)?);
+        }
+        Ok(headers)
+    }
+
+    pub fn construct_url(&self, endpoint: String) -> Result<Url, OpenApiError> {
+        self.base_url
+            .join(endpoint.trim_start_matches('/'))
+            .map_err(OpenApiError::from)
+    }
+
+    /// Resolves the url and payload sent for `endpoint`, rewriting both when in Azure mode.
+    pub fn prepare_request(
+        &self,
+        endpoint: String,
+        body: Option<Value>,
+    ) -> Result<(Url, Option<Value>), OpenApiError> {
+        match &self.azure {
+            Some(azure) => azure.rewrite_request(&self.base_url, endpoint, body),
+            None => Ok((self.construct_url(endpoint)?, body)),
+        }
+    }
+}
+
+/// Parses the value of header `name`, without echoing the value in the error.
+pub(crate) fn header_value(name: &str, value: &str) -> Result<HeaderValue, OpenApiError> {
+    HeaderValue::from_str(value)
+        .map_err(|_| OpenApiError::ClientError(format!("Invalid value for header {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_base(base_url: &str) -> NetworkingConfig {
+        let base_url = NetworkingConfig::parse_base_url(base_url).unwrap();
+        NetworkingConfig::new(String::from("sk-test"), None, base_url).unwrap()
+    }
+
+    #[test]
+    fn test_default_base_url() {
+        let url = NetworkingConfig::default()
+            .construct_url(String::from("threads/runs"))
+            .unwrap();
+        assert_eq!(url.as_str(), "https://api.openai.com/v1/threads/runs");
+    }
+
+    #[test]
+    fn test_base_path_with_and_without_trailing_slash() {
+        for base in ["http://localhost:8000/v1", "http://localhost:8000/v1/"] {
+            let url = config_with_base(base)
+                .construct_url(String::from("chat/completions"))
+                .unwrap();
+            assert_eq!(url.as_str(), "http://localhost:8000/v1/chat/completions");
+        }
+    }
+
+    #[test]
+    fn test_base_without_path() {
+        let url = config_with_base("http://localhost:11434")
+            .construct_url(String::from("/models"))
+            .unwrap();
+        assert_eq!(url.as_str(), "http://localhost:11434/models");
+    }
+
+    #[test]
+    fn test_apikey_not_leaked() {
+        let config = config_with_base("http://localhost:8000/v1");
+        assert!(!format!("{:?}", config).contains("sk-test"));
+        assert!(!serde_json::to_string(&config).unwrap().contains("sk-test"));
+        assert!(!format!("{:?}", config.construct_headers(None).unwrap()).contains("sk-test"));
+    }
+
+    #[test]
+    fn test_invalid_header_values_rejected() {
+        let base_url = NetworkingConfig::parse_base_url(DEFAULT_BASE_URL).unwrap();
+        let err = NetworkingConfig::new("sk-test\n", None, base_url.clone()).unwrap_err();
+        assert!(matches!(err, OpenApiError::ClientError(msg) if msg == "Invalid api key"));
+        let org = Some(String::from("org\r\nX-Injected: 1"));
+        let err = NetworkingConfig::new("sk-test", org, base_url.clone()).unwrap_err();
+        assert!(
+            matches!(err, OpenApiError::ClientError(msg) if msg.contains("OpenAI-Organization"))
+        );
+
+        let config = NetworkingConfig::new("sk-test", None, base_url).unwrap();
+        let headers = |name: &str, value: &str| {
+            config
+                .clone()
+                .with_extra_headers(vec![(name.to_string(), value.to_string())])
+        };
+        assert!(headers("OpenAI-Project", "proj_abc").is_ok());
+        assert!(headers("Bad Name", "proj_abc").is_err());
+        assert!(headers("OpenAI-Project", "proj\nabc").is_err());
+    }
+
+    #[test]
+    fn test_invalid_base_url() {
+        assert!(NetworkingConfig::parse_base_url("not a url").is_err());
+        assert!(NetworkingConfig::parse_base_url("mailto:someone@example.com").is_err());
+    }
+}
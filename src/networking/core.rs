@@ -1,65 +1,108 @@
 use std::collections::HashMap;
-use std::fmt::format;
-use std::hash::Hash;
-use std::path::PathBuf;
-use std::string::ToString;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-use serde_json::{Map, Value};
+use serde_json::Value;
 
-use crate::types::assistant::{Assistant, AssistantBuilder, AssistantFile, AssistantFileBuilder};
-use crate::types::chat::{ChatCompletion, ChatCompletionBuilder};
-use crate::types::common::{ApiList, DeletionStatus, Identifiable};
 use crate::types::error::OpenApiError;
-use crate::types::file::{File, FileBuilder};
-use crate::types::message::{Message, MessageBuilder, MessageFile};
-use crate::types::model::Model;
 use crate::types::moderation::Moderation;
-use crate::types::run::{Run, RunBuilder, RunStep};
-use crate::types::thread::{Thread, ThreadBuilder};
 
-use crate::file::FileTypes;
-use crate::strip_edges;
-use reqwest::blocking::multipart;
-use reqwest::{blocking::Client, header::HeaderMap, Method, Url};
+use reqwest::Method;
 
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+use crate::networking::config::NetworkingConfig;
+use crate::networking::logging;
+use crate::networking::transport::{
+    ReqwestTransport, StreamingResponse, Transport, TransportRequest, TransportResponse,
+};
+use crate::networking::{
+    AssistantsVersion, MultipartForm, RateLimiter, Reply, ResponseMetadata, RetryPolicy,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Networking {
-    apikey: String,
-    organization_id: Option<String>,
+    #[serde(flatten)]
+    config: NetworkingConfig,
+    #[serde(skip, default = "default_transport")]
+    transport: Arc<dyn Transport>,
     #[serde(skip)]
-    client: Client,
+    last_response: Arc<Mutex<Option<ResponseMetadata>>>,
+}
+
+fn default_transport() -> Arc<dyn Transport> {
+    Arc::new(ReqwestTransport::default())
+}
+
+impl Default for Networking {
+    fn default() -> Self {
+        Self::new(NetworkingConfig::default())
+    }
 }
 
 impl Networking {
-    /** ---- Common Networking ---- */
+    /* ---- Common Networking ---- */
+
+    pub(crate) fn new(config: NetworkingConfig) -> Self {
+        Self {
+            config,
+            transport: default_transport(),
+            last_response: Arc::default(),
+        }
+    }
+
+    /// Dispatches every request through `transport` instead of the default reqwest client.
+    pub fn with_transport<T: Transport + 'static>(self, transport: T) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            ..self
+        }
+    }
+
+    /// Sets the Assistants api version sent as `OpenAI-Beta`, by default `AssistantsVersion::V2`.
+    pub fn with_assistants_version(self, assistants_version: AssistantsVersion) -> Self {
+        Self {
+            config: self.config.with_assistants_version(assistants_version),
+            ..self
+        }
+    }
 
-    pub fn new(apikey: String, organization_id: Option<String>) -> Self {
+    /// Retries transient failures according to `retry_policy`, by default no retries are made.
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
         Self {
-            apikey,
-            organization_id,
-            client: Client::new(),
+            config: self.config.with_retry_policy(retry_policy),
+            ..self
         }
     }
 
-    fn construct_headers(&self) -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", self.apikey).parse().unwrap(),
-        );
-        headers.insert("OpenAI-Beta", "assistants=v1".parse().unwrap());
-        if let Some(org_id) = &self.organization_id {
-            headers.insert("OpenAI-Organization", org_id.parse().unwrap());
+    /// Returns the model configured as the default, e.g. from a profile.
+    pub fn get_default_model(&self) -> Option<&str> {
+        self.config.get_default_model()
+    }
+
+    /// Returns the metadata of the most recent response, including error responses. Clones of
+    /// this instance share the same record, so with concurrent calls the last to complete wins.
+    pub fn last_response_metadata(&self) -> Option<ResponseMetadata> {
+        self.last_response.lock().unwrap().clone()
+    }
+
+    /// Paces requests through `rate_limiter` before they are sent, see `RateLimiter`.
+    pub fn with_rate_limiter(self, rate_limiter: RateLimiter) -> Self {
+        Self {
+            config: self.config.with_rate_limiter(rate_limiter),
+            ..self
         }
-        headers
     }
 
-    fn construct_url(&self, endpoint: String) -> Result<Url, OpenApiError> {
-        let base_uri: &str = "https://api.openai.com/v1/";
-        Url::parse((base_uri.to_string() + endpoint.as_str()).as_str()).map_err(OpenApiError::from)
+    /// Emits request and response bodies as `tracing` trace events, disabled by default as bodies
+    /// may contain user content.
+    pub fn with_body_logging(self, log_bodies: bool) -> Self {
+        Self {
+            config: self.config.with_body_logging(log_bodies),
+            ..self
+        }
     }
 
     fn send_request(
@@ -67,23 +110,97 @@ impl Networking {
         method: Method,
         endpoint: String,
         body: Option<Value>,
-        multipart_form: Option<multipart::Form>,
+        multipart_form: Option<MultipartForm>,
     ) -> Result<Value, OpenApiError> {
-        let url = self.construct_url(endpoint)?;
-        let mut request_builder = self
-            .client
-            .request(method, url)
-            .headers(self.construct_headers());
-        if let Some(b) = body {
-            request_builder = request_builder
-                .body(serde_json::to_string(&b)?)
-                .header("Content-Type", "application/json")
+        match self.dispatch(method, endpoint, body, multipart_form, false)? {
+            Reply::Json(value) => Ok(value),
+            Reply::Stream(_) => unreachable!("only streamed requests return a stream"),
         }
-        if let Some(mf) = multipart_form {
-            request_builder = request_builder.multipart(mf)
+    }
+
+    /// Sends a request whose response body is read incrementally, e.g. a streamed chat
+    /// completion. Failed attempts are retried as usual, but only until the response has started.
+    pub fn send_streaming(
+        &self,
+        method: Method,
+        endpoint: String,
+        body: Option<Value>,
+    ) -> Result<StreamingResponse, OpenApiError> {
+        match self.dispatch(method, endpoint, body, None, true)? {
+            Reply::Stream(response) => Ok(response),
+            Reply::Json(_) => unreachable!("streamed requests always return a stream"),
         }
-        let res = request_builder.send()?;
-        res.json().map_err(OpenApiError::from)
+    }
+
+    fn dispatch(
+        &self,
+        method: Method,
+        endpoint: String,
+        body: Option<Value>,
+        multipart_form: Option<MultipartForm>,
+        stream: bool,
+    ) -> Result<Reply<StreamingResponse>, OpenApiError> {
+        let _span = logging::request_span(&method, &endpoint).entered();
+        let (url, body) = self.config.prepare_request(endpoint.clone(), body)?;
+        let mut attempt: u32 = 1;
+        loop {
+            let (permit, delay) = self.config.throttle(&endpoint, &body);
+            if !delay.is_zero() {
+                std::thread::sleep(delay);
+            }
+            let lease = self.config.lease_key();
+            let request = self.config.build_request(
+                lease.as_ref(),
+                method.clone(),
+                url.clone(),
+                &body,
+                &multipart_form,
+            );
+            let result = match request {
+                Ok(request) => self.send_attempt(attempt, request, stream),
+                Err(err) => Err(err),
+            };
+            match self
+                .config
+                .settle_attempt(attempt, &method, lease, permit, &result)
+            {
+                Some(delay) => {
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return result,
+            }
+        }
+    }
+
+    /// A single attempt of a request, streamed responses are only read in full on failure.
+    fn send_attempt(
+        &self,
+        attempt: u32,
+        request: TransportRequest,
+        stream: bool,
+    ) -> Result<Reply<StreamingResponse>, OpenApiError> {
+        let started = Instant::now();
+        let res = match stream {
+            true => {
+                let mut res = self.transport.send_streaming(request)?;
+                if res.status.is_success() {
+                    let metadata =
+                        self.config
+                            .stream_started(attempt, res.status, &res.headers, started);
+                    *self.last_response.lock().unwrap() = Some(metadata);
+                    return Ok(Reply::Stream(res));
+                }
+                // Errors are sent as a regular JSON body, so read it in full
+                let mut body = Vec::new();
+                res.body.read_to_end(&mut body)?;
+                TransportResponse::new(res.status, res.headers, body)
+            }
+            false => self.transport.send(request)?,
+        };
+        let (metadata, result) = self.config.read_response(attempt, &res, started);
+        *self.last_response.lock().unwrap() = Some(metadata);
+        result.map(Reply::Json)
     }
 
     pub fn send_and_convert<T: DeserializeOwned>(
@@ -91,17 +208,13 @@ impl Networking {
         method: Method,
         endpoint: String,
         body: Option<Value>,
-        multipart_form: Option<multipart::Form>,
+        multipart_form: Option<MultipartForm>,
     ) -> Result<T, OpenApiError> {
-        self.send_request(method, endpoint.clone(), body, multipart_form)
-            .and_then(|val| {
-                println!("[{0}] - {1}", endpoint, serde_json::to_string(&val)?);
-                Ok(val)
-            })
+        self.send_request(method, endpoint, body, multipart_form)
             .and_then(|val| serde_json::from_value::<T>(val).map_err(OpenApiError::from))
     }
 
-    /** ---- Moderation ----
+    /* ---- Moderation ----
      * No builder for create_moderation, uses client method
      */
 
@@ -117,3 +230,98 @@ impl Networking {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networking::fixtures;
+    use crate::networking::transport::MockTransport;
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    fn networking(mock: &MockTransport, retry_policy: RetryPolicy) -> Networking {
+        Networking::new(fixtures::config(retry_policy)).with_transport(mock.clone())
+    }
+
+    #[test]
+    fn test_error_response_decoded() {
+        let mock = fixtures::not_found_mock();
+        fixtures::assert_not_found(networking(&mock, RetryPolicy::none()).send_and_convert(
+            Method::GET,
+            "models/gpt-5".into(),
+            None,
+            None,
+        ));
+    }
+
+    #[test]
+    fn test_key_pool_rotates_after_rate_limit() {
+        let (pool, mock) = fixtures::rate_limited_pool();
+        let config = NetworkingConfig::default().with_key_pool(pool.clone());
+        let networking = Networking::new(config).with_transport(mock.clone());
+        let _: Value = networking
+            .send_and_convert(Method::GET, "models".into(), None, None)
+            .unwrap();
+        fixtures::assert_key_rotated(&pool, &mock);
+    }
+
+    #[test]
+    fn test_transient_failure_retried() {
+        let mock = fixtures::transient_failure_mock();
+        let networking = networking(&mock, fixtures::single_retry());
+        let value: Value = networking
+            .send_and_convert(Method::GET, "models".into(), None, None)
+            .unwrap();
+        let metadata = networking.last_response_metadata().unwrap();
+        fixtures::assert_retried(&mock, value, metadata);
+    }
+
+    #[test]
+    fn test_list_params_sent_as_query() {
+        use crate::networking::MessageActions;
+        use crate::types::common::ListParams;
+
+        let (mock, params) = fixtures::messages_page();
+        let messages = networking(&mock, RetryPolicy::none())
+            .list_messages("thread_abc".into(), Some(&params))
+            .unwrap();
+        fixtures::assert_listed_with_query(&mock, messages);
+        assert!(ListParams::new().with_limit(101).is_err());
+    }
+
+    #[test]
+    fn test_chat_completion_streamed() {
+        use crate::types::chat::ChatCompletionBuilder;
+
+        let mock = fixtures::chat_stream_mock();
+        let chunks = ChatCompletionBuilder::new("gpt-4", Vec::new())
+            .with_include_usage(true)
+            .build_stream(&networking(&mock, RetryPolicy::none()))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        fixtures::assert_streamed(&mock, &chunks);
+    }
+
+    #[test]
+    fn test_stream_options_left_out_when_not_streaming() {
+        use crate::types::chat::ChatCompletionBuilder;
+
+        let completion = json!({"id": "chatcmpl-1", "object": "chat.completion", "created": 1, "model": "gpt-4", "system_fingerprint": null, "choices": [], "usage": null});
+        let mock = MockTransport::new()
+            .with_json(
+                Method::POST,
+                "chat/completions",
+                StatusCode::OK,
+                &completion,
+            )
+            .unwrap();
+        ChatCompletionBuilder::new("gpt-4", Vec::new())
+            .with_include_usage(true)
+            .build(&networking(&mock, RetryPolicy::none()))
+            .unwrap();
+
+        let request = mock.requests()[0].json_body().unwrap().clone();
+        assert!(request.get("stream_options").is_none());
+    }
+}
@@ -1,15 +1,14 @@
-use crate::common::{ApiList, DeletionStatus};
+use crate::common::{ApiList, DeletionStatus, ListParams};
 use crate::error::OpenApiError;
 use crate::file::{File, FileTypes};
-use crate::networking::Networking;
+use crate::networking::{MultipartForm, Networking};
 use crate::strip_edges;
-use reqwest::blocking::multipart;
 use reqwest::Method;
 use std::path::PathBuf;
 
 pub trait FileActions {
     fn upload_file(&self, file: PathBuf, purpose: FileTypes) -> Result<File, OpenApiError>;
-    fn list_files(&self) -> Result<ApiList<File>, OpenApiError>;
+    fn list_files(&self, params: Option<&ListParams>) -> Result<ApiList<File>, OpenApiError>;
     fn retrieve_file(&self, file_id: String) -> Result<File, OpenApiError>;
     fn delete_file(&self, file_id: String) -> Result<DeletionStatus, OpenApiError>;
     fn retrieve_file_content(&self, file_id: String) -> Result<String, OpenApiError>;
@@ -17,15 +16,20 @@ pub trait FileActions {
 
 impl FileActions for Networking {
     fn upload_file(&self, file: PathBuf, purpose: FileTypes) -> Result<File, OpenApiError> {
-        let form = multipart::Form::new()
+        let form = MultipartForm::new()
             .text("purpose", strip_edges!(serde_json::to_string(&purpose)?))
-            .file("file", file.as_path())?;
+            .file("file", file);
 
         self.send_and_convert(Method::POST, String::from("files"), None, Some(form))
     }
 
-    fn list_files(&self) -> Result<ApiList<File>, OpenApiError> {
-        self.send_and_convert(Method::GET, String::from("files"), None, None)
+    fn list_files(&self, params: Option<&ListParams>) -> Result<ApiList<File>, OpenApiError> {
+        self.send_and_convert(
+            Method::GET,
+            ListParams::apply(String::from("files"), params)?,
+            None,
+            None,
+        )
     }
 
     fn retrieve_file(&self, file_id: String) -> Result<File, OpenApiError> {
@@ -46,5 +50,3 @@ impl FileActions for Networking {
         )
     }
 }
-
-impl FileActions for File {}
@@ -0,0 +1,148 @@
+//! Mocked exchanges shared by the blocking `Networking` and async `AsyncNetworking` tests, so both
+//! cores are checked against the same responses and expectations.
+
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+use crate::networking::config::NetworkingConfig;
+use crate::networking::transport::{MockTransport, TransportResponse};
+use crate::networking::{KeyPool, ResponseMetadata, RetryPolicy};
+use crate::types::chat::ChatCompletionChunk;
+use crate::types::common::{ApiList, ListOrder, ListParams};
+use crate::types::error::OpenApiError;
+use crate::types::message::Message;
+
+pub(crate) fn config(retry_policy: RetryPolicy) -> NetworkingConfig {
+    NetworkingConfig::default().with_retry_policy(retry_policy)
+}
+
+/// `models/gpt-5` answered with a 404 error envelope.
+pub(crate) fn not_found_mock() -> MockTransport {
+    let body = json!({"error": {"message": "No such model", "type": "invalid_request_error", "code": "model_not_found"}});
+    MockTransport::new()
+        .with_json(Method::GET, "models/gpt-5", StatusCode::NOT_FOUND, &body)
+        .unwrap()
+}
+
+pub(crate) fn assert_not_found(result: Result<Value, OpenApiError>) {
+    match result {
+        Err(OpenApiError::Api(err)) => assert_eq!(err.get_status(), StatusCode::NOT_FOUND),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+/// A pool of two keys, with `models` rate limited on the first attempt.
+pub(crate) fn rate_limited_pool() -> (KeyPool, MockTransport) {
+    let pool = KeyPool::new()
+        .add_key("sk-first-0000000001", None)
+        .unwrap()
+        .add_key("sk-second-000000002", None)
+        .unwrap();
+    let mock = MockTransport::new()
+        .with_json(
+            Method::GET,
+            "models",
+            StatusCode::TOO_MANY_REQUESTS,
+            &json!({}),
+        )
+        .unwrap()
+        .with_json(Method::GET, "models", StatusCode::OK, &json!({}))
+        .unwrap();
+    (pool, mock)
+}
+
+pub(crate) fn assert_key_rotated(pool: &KeyPool, mock: &MockTransport) {
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let auth = |i: usize| requests[i].headers.get("Authorization").unwrap().clone();
+    assert_eq!(auth(0), "Bearer sk-first-0000000001");
+    assert_eq!(auth(1), "Bearer sk-second-000000002");
+    assert_eq!(pool.stats()[0].get_rate_limited(), 1);
+}
+
+/// Retries once without waiting.
+pub(crate) fn single_retry() -> RetryPolicy {
+    RetryPolicy::new(2)
+        .with_initial_backoff(Duration::ZERO)
+        .with_jitter(false)
+}
+
+/// `models` answered with a 503, then with a list.
+pub(crate) fn transient_failure_mock() -> MockTransport {
+    MockTransport::new()
+        .with_json(
+            Method::GET,
+            "models",
+            StatusCode::SERVICE_UNAVAILABLE,
+            &json!({}),
+        )
+        .unwrap()
+        .with_json(
+            Method::GET,
+            "models",
+            StatusCode::OK,
+            &json!({"object": "list"}),
+        )
+        .unwrap()
+}
+
+pub(crate) fn assert_retried(mock: &MockTransport, value: Value, metadata: ResponseMetadata) {
+    assert_eq!(value["object"], "list");
+    assert_eq!(metadata.get_status(), StatusCode::OK);
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].url.as_str(), "https://api.openai.com/v1/models");
+    assert!(requests[0].headers.contains_key("Authorization"));
+}
+
+/// An empty page of messages for `thread_abc`, with the params expected in its query.
+pub(crate) fn messages_page() -> (MockTransport, ListParams) {
+    let page =
+        json!({"object": "list", "data": [], "first_id": null, "last_id": null, "has_more": false});
+    let mock = MockTransport::new()
+        .with_json(
+            Method::GET,
+            "threads/thread_abc/messages",
+            StatusCode::OK,
+            &page,
+        )
+        .unwrap();
+    let params = ListParams::new()
+        .with_limit(100)
+        .unwrap()
+        .with_order(ListOrder::Asc)
+        .with_after("msg_abc");
+    (mock, params)
+}
+
+pub(crate) fn assert_listed_with_query(mock: &MockTransport, messages: ApiList<Message>) {
+    assert!(!messages.has_more());
+    assert_eq!(
+        mock.requests()[0].url.query(),
+        Some("limit=100&order=asc&after=msg_abc")
+    );
+}
+
+/// `chat/completions` streamed as a single chunk.
+pub(crate) fn chat_stream_mock() -> MockTransport {
+    let body = concat!(
+        r#"data: {"id":"chatcmpl-1","object":"chat.completion.chunk","created":1,"model":"gpt-4","system_fingerprint":null,"choices":[{"index":0,"delta":{"role":"assistant","content":"Hi"},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        "data: [DONE]\n\n"
+    );
+    let response = TransportResponse::new(StatusCode::OK, HeaderMap::new(), body.into());
+    MockTransport::new().with_response(Method::POST, "chat/completions", response)
+}
+
+pub(crate) fn assert_streamed(mock: &MockTransport, chunks: &[ChatCompletionChunk]) {
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].get_delta_content(), Some("Hi"));
+
+    let request = mock.requests()[0].json_body().unwrap().clone();
+    assert_eq!(request["stream"], true);
+    assert_eq!(request["stream_options"]["include_usage"], true);
+}
@@ -0,0 +1,426 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderValue;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use crate::credentials::ApiKey;
+use crate::networking::config::header_value;
+use crate::types::error::OpenApiError;
+
+/// How long a key is benched after a 429, unless the response asks for longer.
+const DEFAULT_RATE_LIMIT_BENCH: Duration = Duration::from_secs(60);
+
+/// How long a key is benched after a 401.
+const DEFAULT_UNAUTHORIZED_BENCH: Duration = Duration::from_secs(60 * 60);
+
+/// The bench used when the configured or requested one reaches past what `Instant` can represent.
+const MAX_BENCH: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How requests are distributed between the keys of a `KeyPool`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoolStrategy {
+    /// Every key is used in turn.
+    #[default]
+    RoundRobin,
+    /// The key with the fewest requests in flight is used.
+    LeastLoaded,
+}
+
+/// `KeyPool` spreads requests over several (apikey, organization) credentials. A key is benched
+/// for a while after a 429 or 401 response, during which the other keys are used. Clones share
+/// the same keys and statistics.
+///
+/// # Fields
+///
+/// * `state: Arc<Mutex<PoolState>>` - The credentials and their statistics, shared between clones.
+///
+/// # Examples
+/// ```ignore
+/// let pool = KeyPool::new()
+///     .add_key("sk-first", Some("org-first".into()))?
+///     .add_key("sk-second", None)?
+///     .with_strategy(PoolStrategy::LeastLoaded);
+/// let client = OpenAIClient::new_with_key_pool(pool.clone())?;
+/// for stats in pool.stats() {
+///     println!("{} {} {}", stats.get_label(), stats.get_requests(), stats.get_total_tokens());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct KeyPool {
+    state: Arc<Mutex<PoolState>>,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    keys: Vec<PooledKey>,
+    strategy: PoolStrategy,
+    next: usize,
+    rate_limit_bench: Duration,
+    unauthorized_bench: Duration,
+}
+
+impl Default for PoolState {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            strategy: PoolStrategy::default(),
+            next: 0,
+            rate_limit_bench: DEFAULT_RATE_LIMIT_BENCH,
+            unauthorized_bench: DEFAULT_UNAUTHORIZED_BENCH,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PooledKey {
+    credential: Credential,
+    benched_until: Option<Instant>,
+    stats: KeyStats,
+}
+
+impl PooledKey {
+    fn is_benched(&self, now: Instant) -> bool {
+        self.benched_until.map(|until| until > now).unwrap_or(false)
+    }
+}
+
+/// An api key and the organization its requests are billed to.
+#[derive(Clone, Debug)]
+pub(crate) struct Credential {
+    pub(crate) apikey: ApiKey,
+    pub(crate) organization_id: Option<String>,
+}
+
+/// `KeyStats` holds the usage of a single key of a `KeyPool`.
+///
+/// # Fields
+///
+/// * `label: String` - A redacted form of the key, e.g. `sk-...wxyz`, identifying it in logs.
+/// * `organization_id: Option<String>` - The organization of the key.
+/// * `requests: u64` - The number of requests sent with the key, including retries.
+/// * `failures: u64` - The number of requests that failed.
+/// * `rate_limited: u64` - The number of 429 responses received.
+/// * `unauthorized: u64` - The number of 401 responses received.
+/// * `in_flight: u32` - The number of requests currently awaiting a response.
+/// * `total_tokens: u64` - The tokens reported in the `usage` of responses.
+/// * `benched_for: Option<Duration>` - The time until the key is used again, if benched.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyStats {
+    label: String,
+    organization_id: Option<String>,
+    requests: u64,
+    failures: u64,
+    rate_limited: u64,
+    unauthorized: u64,
+    in_flight: u32,
+    total_tokens: u64,
+    benched_for: Option<Duration>,
+}
+
+impl KeyStats {
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn get_organization_id(&self) -> Option<&str> {
+        self.organization_id.as_deref()
+    }
+
+    pub fn get_requests(&self) -> u64 {
+        self.requests
+    }
+
+    pub fn get_failures(&self) -> u64 {
+        self.failures
+    }
+
+    pub fn get_rate_limited(&self) -> u64 {
+        self.rate_limited
+    }
+
+    pub fn get_unauthorized(&self) -> u64 {
+        self.unauthorized
+    }
+
+    pub fn get_in_flight(&self) -> u32 {
+        self.in_flight
+    }
+
+    pub fn get_total_tokens(&self) -> u64 {
+        self.total_tokens
+    }
+
+    pub fn get_benched_for(&self) -> Option<Duration> {
+        self.benched_for
+    }
+}
+
+/// A key checked out of the pool for a single attempt, returned to the pool when dropped.
+#[derive(Debug)]
+pub(crate) struct KeyLease {
+    pool: KeyPool,
+    index: usize,
+    credential: Credential,
+}
+
+impl KeyPool {
+    pub fn new() -> Self {
+        KeyPool::default()
+    }
+
+    /// Adds a key, failing with `OpenApiError::ClientError` when the key or organization id cannot
+    /// be sent as a header.
+    pub fn add_key<A: Into<String>>(
+        self,
+        apikey: A,
+        organization_id: Option<String>,
+    ) -> Result<Self, OpenApiError> {
+        let apikey = ApiKey::from(apikey.into());
+        HeaderValue::from_str(apikey.expose())
+            .map_err(|_| OpenApiError::ClientError(String::from("Invalid api key")))?;
+        if let Some(organization_id) = &organization_id {
+            header_value("OpenAI-Organization", organization_id)?;
+        }
+        let stats = KeyStats {
+            label: apikey.hint(),
+            organization_id: organization_id.clone(),
+            ..KeyStats::default()
+        };
+        self.state.lock().unwrap().keys.push(PooledKey {
+            credential: Credential {
+                apikey,
+                organization_id,
+            },
+            benched_until: None,
+            stats,
+        });
+        Ok(self)
+    }
+
+    pub fn with_strategy(self, strategy: PoolStrategy) -> Self {
+        self.state.lock().unwrap().strategy = strategy;
+        self
+    }
+
+    /// Sets how long a key is benched after a 429, by default a minute. A longer delay requested
+    /// by the response is respected.
+    pub fn with_rate_limit_bench(self, bench: Duration) -> Self {
+        self.state.lock().unwrap().rate_limit_bench = bench;
+        self
+    }
+
+    /// Sets how long a key is benched after a 401, by default an hour.
+    pub fn with_unauthorized_bench(self, bench: Duration) -> Self {
+        self.state.lock().unwrap().unauthorized_bench = bench;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the statistics of every key, in the order the keys were added.
+    pub fn stats(&self) -> Vec<KeyStats> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        state
+            .keys
+            .iter()
+            .map(|key| KeyStats {
+                benched_for: key
+                    .benched_until
+                    .filter(|_| key.is_benched(now))
+                    .map(|until| until - now),
+                ..key.stats.clone()
+            })
+            .collect()
+    }
+
+    /// Returns whether any key is currently available, i.e. not benched.
+    pub(crate) fn has_available_key(&self) -> bool {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        state.keys.iter().any(|key| !key.is_benched(now))
+    }
+
+    /// Checks out a key according to the strategy, skipping benched keys. When every key is
+    /// benched the one returning soonest is used. Returns `None` for an empty pool.
+    pub(crate) fn lease(&self) -> Option<KeyLease> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let count = state.keys.len();
+        if count == 0 {
+            return None;
+        }
+        let available = |i: &usize| !state.keys[*i].is_benched(now);
+        let index = match state.strategy {
+            PoolStrategy::RoundRobin => (0..count)
+                .map(|offset| (state.next + offset) % count)
+                .find(available),
+            PoolStrategy::LeastLoaded => (0..count).filter(available).min_by_key(|i| {
+                let stats = &state.keys[*i].stats;
+                (stats.in_flight, stats.requests)
+            }),
+        };
+        let index = index.unwrap_or_else(|| {
+            (0..count)
+                .min_by_key(|i| state.keys[*i].benched_until)
+                .unwrap_or_default()
+        });
+        state.next = (index + 1) % count;
+
+        let key = &mut state.keys[index];
+        key.stats.requests += 1;
+        key.stats.in_flight += 1;
+        Some(KeyLease {
+            pool: self.clone(),
+            index,
+            credential: key.credential.clone(),
+        })
+    }
+}
+
+impl KeyLease {
+    pub(crate) fn credential(&self) -> &Credential {
+        &self.credential
+    }
+
+    /// Records the outcome of the attempt, benching the key after a 429 or 401.
+    pub(crate) fn complete(&self, result: Result<&Value, &OpenApiError>) {
+        let mut state = self.pool.state.lock().unwrap();
+        let (rate_limit_bench, unauthorized_bench) =
+            (state.rate_limit_bench, state.unauthorized_bench);
+        let key = &mut state.keys[self.index];
+        match result {
+            Ok(value) => {
+                let tokens = value
+                    .get("usage")
+                    .and_then(|usage| usage.get("total_tokens"))
+                    .and_then(Value::as_u64);
+                key.stats.total_tokens += tokens.unwrap_or_default();
+            }
+            Err(err) => {
+                key.stats.failures += 1;
+                let bench = match err {
+                    OpenApiError::Api(api_err)
+                        if api_err.get_status() == StatusCode::TOO_MANY_REQUESTS =>
+                    {
+                        key.stats.rate_limited += 1;
+                        let retry_after = api_err.get_retry_after().unwrap_or_default();
+                        Some(rate_limit_bench.max(retry_after))
+                    }
+                    OpenApiError::Api(api_err)
+                        if api_err.get_status() == StatusCode::UNAUTHORIZED =>
+                    {
+                        key.stats.unauthorized += 1;
+                        Some(unauthorized_bench)
+                    }
+                    _ => None,
+                };
+                if let Some(bench) = bench {
+                    tracing::warn!(
+                        key = %key.stats.label,
+                        bench_secs = bench.as_secs(),
+                        "benching openai api key"
+                    );
+                    let now = Instant::now();
+                    key.benched_until = Some(now.checked_add(bench).unwrap_or(now + MAX_BENCH));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for KeyLease {
+    fn drop(&mut self) {
+        let mut state = self.pool.state.lock().unwrap();
+        if let Some(key) = state.keys.get_mut(self.index) {
+            key.stats.in_flight = key.stats.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    fn pool() -> KeyPool {
+        KeyPool::new()
+            .add_key("sk-first-0000000001", None)
+            .unwrap()
+            .add_key("sk-second-000000002", Some("org-second".into()))
+            .unwrap()
+    }
+
+    fn api_error(status: StatusCode) -> Result<Value, OpenApiError> {
+        Err(OpenApiError::from_response(
+            status,
+            &HeaderMap::new(),
+            b"{}",
+        ))
+    }
+
+    #[test]
+    fn test_round_robin_skips_benched_key() {
+        let pool = pool();
+        let first = pool.lease().unwrap();
+        assert_eq!(first.index, 0);
+        first.complete(api_error(StatusCode::TOO_MANY_REQUESTS).as_ref());
+        drop(first);
+
+        assert_eq!(pool.lease().unwrap().index, 1);
+        assert_eq!(pool.lease().unwrap().index, 1);
+
+        let stats = pool.stats();
+        assert_eq!(stats[0].get_rate_limited(), 1);
+        assert!(stats[0].get_benched_for().is_some());
+        assert_eq!(stats[1].get_requests(), 2);
+        assert_eq!(stats[1].get_in_flight(), 0);
+        assert!(!stats[0].get_label().contains("first"));
+    }
+
+    #[test]
+    fn test_least_loaded() {
+        let pool = pool().with_strategy(PoolStrategy::LeastLoaded);
+        let first = pool.lease().unwrap();
+        let second = pool.lease().unwrap();
+        assert_ne!(first.index, second.index);
+        drop(second);
+        // The first key is still in flight, so the second is used again
+        let third = pool.lease().unwrap();
+        assert_eq!(third.index, 1);
+    }
+
+    #[test]
+    fn test_invalid_key_rejected() {
+        assert!(KeyPool::new().add_key("sk-test\n", None).is_err());
+        let org = Some(String::from("org\r\nX-Injected: 1"));
+        assert!(KeyPool::new().add_key("sk-test", org).is_err());
+    }
+
+    #[test]
+    fn test_unbounded_bench_capped() {
+        let pool = pool().with_unauthorized_bench(Duration::MAX);
+        let lease = pool.lease().unwrap();
+        lease.complete(api_error(StatusCode::UNAUTHORIZED).as_ref());
+        let benched_for = pool.stats()[0].get_benched_for().unwrap();
+        assert!(benched_for <= MAX_BENCH);
+        assert!(benched_for > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_tokens_counted_from_usage() {
+        let pool = pool();
+        let lease = pool.lease().unwrap();
+        lease.complete(Ok(&serde_json::json!({"usage": {"total_tokens": 42}})));
+        assert_eq!(pool.stats()[0].get_total_tokens(), 42);
+    }
+}
@@ -0,0 +1,181 @@
+//! `tracing` instrumentation shared by `Networking` and `AsyncNetworking`.
+//!
+//! Every request runs within an `openai_request` span at debug level, carrying the method,
+//! endpoint, status, latency, request id and number of attempts. Request and response bodies may
+//! contain user content, so they are only emitted as trace events once body logging is enabled
+//! with `with_body_logging`.
+
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use tracing::field::Empty;
+use tracing::Span;
+
+use crate::networking::transport::{RequestBody, TransportResponse};
+use crate::types::error::OpenApiError;
+
+pub(crate) fn request_span(method: &Method, endpoint: &str) -> Span {
+    tracing::debug_span!(
+        "openai_request",
+        method = %method,
+        endpoint = %endpoint,
+        status = Empty,
+        latency_ms = Empty,
+        request_id = Empty,
+        attempts = Empty,
+    )
+}
+
+pub(crate) fn log_request_body(body: &Option<RequestBody>, log_bodies: bool) {
+    if !log_bodies {
+        return;
+    }
+    match body {
+        Some(RequestBody::Json(value)) => tracing::trace!(body = %value, "openai request body"),
+        Some(RequestBody::Multipart(_)) => tracing::trace!("openai request body is multipart"),
+        None => {}
+    }
+}
+
+/// Records the outcome of an attempt on the current request span.
+pub(crate) fn record_response(
+    attempt: u32,
+    response: &TransportResponse,
+    latency: Duration,
+    log_bodies: bool,
+) {
+    record_response_head(attempt, response.status, &response.headers, latency);
+    if log_bodies {
+        tracing::trace!(
+            body = %String::from_utf8_lossy(&response.body),
+            "openai response body"
+        );
+    }
+}
+
+/// Records the outcome of an attempt whose body is streamed, and so never logged.
+pub(crate) fn record_response_head(
+    attempt: u32,
+    status: StatusCode,
+    headers: &HeaderMap,
+    latency: Duration,
+) {
+    let request_id = headers
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let latency_ms = latency.as_millis() as u64;
+
+    let span = Span::current();
+    span.record("status", status.as_u16());
+    span.record("latency_ms", latency_ms);
+    span.record("request_id", request_id);
+    span.record("attempts", attempt);
+
+    tracing::debug!(
+        attempt,
+        status = status.as_u16(),
+        latency_ms,
+        request_id,
+        "openai response"
+    );
+}
+
+pub(crate) fn log_retry(attempt: u32, delay: Duration, err: &OpenApiError) {
+    tracing::warn!(
+        attempt,
+        delay_ms = delay.as_millis() as u64,
+        error = %err,
+        "retrying openai request"
+    );
+}
+
+pub(crate) fn log_throttle(delay: Duration) {
+    tracing::debug!(
+        delay_ms = delay.as_millis() as u64,
+        "waiting for openai rate limiter"
+    );
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use reqwest::{Method, StatusCode, Url};
+    use serde_json::{json, Value};
+    use tracing::Level;
+
+    use crate::networking::transport::MockTransport;
+    use crate::networking::{Networking, NetworkingConfig};
+
+    /// Collects everything the subscriber writes.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Sends a chat completion with `log_bodies`, returning the logs it produced.
+    fn logged_request(log_bodies: bool) -> String {
+        let mock = MockTransport::new()
+            .with_json(
+                Method::POST,
+                "chat/completions",
+                StatusCode::OK,
+                &json!({"answer": "the response text"}),
+            )
+            .unwrap();
+        let base_url = Url::parse("https://api.openai.com/v1/").unwrap();
+        let config = NetworkingConfig::new(String::from("sk-secret-key-0001"), None, base_url)
+            .unwrap()
+            .with_body_logging(log_bodies);
+        let networking = Networking::new(config).with_transport(mock);
+
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let body = json!({"prompt": "the request text"});
+            let _: Value = networking
+                .send_and_convert(Method::POST, "chat/completions".into(), Some(body), None)
+                .unwrap();
+        });
+        let logs = capture.0.lock().unwrap().clone();
+        String::from_utf8(logs).unwrap()
+    }
+
+    #[test]
+    fn test_request_span_recorded_without_key() {
+        let logs = logged_request(true);
+        assert!(logs.contains("openai_request"));
+        assert!(logs.contains("endpoint=chat/completions"));
+        assert!(logs.contains("status=200"));
+        assert!(!logs.contains("sk-secret-key-0001"));
+    }
+
+    #[test]
+    fn test_bodies_logged_only_when_enabled() {
+        let logs = logged_request(false);
+        assert!(logs.contains("openai response"));
+        assert!(!logs.contains("the request text"));
+        assert!(!logs.contains("the response text"));
+
+        let logs = logged_request(true);
+        assert!(logs.contains("the request text"));
+        assert!(logs.contains("the response text"));
+    }
+}
@@ -1,46 +1,50 @@
-use crate::common::ApiList;
+use crate::common::{ApiList, ListParams};
 use crate::error::OpenApiError;
 use crate::message::{Message, MessageBuilder, MessageFile};
 use crate::networking::Networking;
 use reqwest::Method;
-use std::collections::HashMap;
 
 pub trait MessageActions {
     fn create_message(
         &self,
         payload: &MessageBuilder,
-        thread_id: &String,
+        thread_id: &str,
     ) -> Result<Message, OpenApiError>;
-    fn list_messages(&self, thread_id: String) -> Result<ApiList<Message>, OpenApiError>;
+    fn list_messages(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Message>, OpenApiError>;
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
     fn list_message_files(
         &self,
         thread_id: String,
         message_id: String,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<MessageFile>, OpenApiError>;
     fn retrieve_message(
         &self,
         thread_id: String,
         message_id: String,
     ) -> Result<Message, OpenApiError>;
+    #[deprecated(
+        note = "message files were removed in Assistants v2, use `MessageBuilder::with_attachments`"
+    )]
     fn retrieve_message_file(
         &self,
         thread_id: String,
         message_id: String,
         file_id: String,
     ) -> Result<MessageFile, OpenApiError>;
-    fn modify_message(
-        &self,
-        thread_id: String,
-        message_id: String,
-        metadata: HashMap<String, String>,
-    ) -> Result<Message, OpenApiError>;
 }
 
 impl MessageActions for Networking {
     fn create_message(
         &self,
         payload: &MessageBuilder,
-        thread_id: &String,
+        thread_id: &str,
     ) -> Result<Message, OpenApiError> {
         self.send_and_convert(
             Method::POST,
@@ -50,10 +54,14 @@ impl MessageActions for Networking {
         )
     }
 
-    fn list_messages(&self, thread_id: String) -> Result<ApiList<Message>, OpenApiError> {
+    fn list_messages(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Message>, OpenApiError> {
         self.send_and_convert(
             Method::GET,
-            format!("threads/{}/messages", thread_id),
+            ListParams::apply(format!("threads/{}/messages", thread_id), params)?,
             None,
             None,
         )
@@ -63,10 +71,14 @@ impl MessageActions for Networking {
         &self,
         thread_id: String,
         message_id: String,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<MessageFile>, OpenApiError> {
         self.send_and_convert(
             Method::GET,
-            format!("threads/{0}/messages/{1}/files", thread_id, message_id),
+            ListParams::apply(
+                format!("threads/{0}/messages/{1}/files", thread_id, message_id),
+                params,
+            )?,
             None,
             None,
         )
@@ -101,18 +113,4 @@ impl MessageActions for Networking {
             None,
         )
     }
-
-    fn modify_message(
-        &self,
-        thread_id: String,
-        message_id: String,
-        metadata: HashMap<String, String>,
-    ) -> Result<Message, OpenApiError> {
-        self.send_and_convert(
-            Method::POST,
-            format!("threads/{0}/messages/{1}", thread_id, message_id),
-            Some(serde_json::to_value(&metadata)?),
-            None,
-        )
-    }
 }
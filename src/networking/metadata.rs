@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+use crate::networking::retry::parse_reset_duration;
+
+/// `ResponseMetadata` holds the headers OpenAI attaches to every response, recorded for the most
+/// recent call and available through `last_response_metadata` on the client.
+///
+/// # Fields
+///
+/// * `status: StatusCode` - The status of the response.
+/// * `request_id: Option<String>` - The `x-request-id` of the request, useful when contacting support.
+/// * `processing_time: Option<Duration>` - The time spent processing the request, from `openai-processing-ms`.
+/// * `rate_limit: RateLimit` - The rate limit state after the request.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseMetadata {
+    status: StatusCode,
+    request_id: Option<String>,
+    processing_time: Option<Duration>,
+    rate_limit: RateLimit,
+}
+
+/// `RateLimit` holds the `x-ratelimit-*` headers, any header missing from the response or
+/// failing to parse is `None`.
+///
+/// # Fields
+///
+/// * `limit_requests: Option<u64>` - The maximum number of requests permitted before exhausting the rate limit.
+/// * `limit_tokens: Option<u64>` - The maximum number of tokens permitted before exhausting the rate limit.
+/// * `remaining_requests: Option<u64>` - The remaining number of requests permitted.
+/// * `remaining_tokens: Option<u64>` - The remaining number of tokens permitted.
+/// * `reset_requests: Option<Duration>` - The time until the request limit resets to its initial state.
+/// * `reset_tokens: Option<Duration>` - The time until the token limit resets to its initial state.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateLimit {
+    limit_requests: Option<u64>,
+    limit_tokens: Option<u64>,
+    remaining_requests: Option<u64>,
+    remaining_tokens: Option<u64>,
+    reset_requests: Option<Duration>,
+    reset_tokens: Option<Duration>,
+}
+
+impl ResponseMetadata {
+    pub(crate) fn from_headers(status: StatusCode, headers: &HeaderMap) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+        };
+        let number = |name: &str| header(name).and_then(|v| v.parse::<u64>().ok());
+        let reset = |name: &str| header(name).and_then(|v| parse_reset_duration(&v));
+
+        Self {
+            status,
+            request_id: header("x-request-id"),
+            processing_time: number("openai-processing-ms").map(Duration::from_millis),
+            rate_limit: RateLimit {
+                limit_requests: number("x-ratelimit-limit-requests"),
+                limit_tokens: number("x-ratelimit-limit-tokens"),
+                remaining_requests: number("x-ratelimit-remaining-requests"),
+                remaining_tokens: number("x-ratelimit-remaining-tokens"),
+                reset_requests: reset("x-ratelimit-reset-requests"),
+                reset_tokens: reset("x-ratelimit-reset-tokens"),
+            },
+        }
+    }
+
+    pub fn get_status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn get_request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn get_processing_time(&self) -> Option<Duration> {
+        self.processing_time
+    }
+
+    pub fn get_rate_limit(&self) -> &RateLimit {
+        &self.rate_limit
+    }
+}
+
+impl RateLimit {
+    pub fn get_limit_requests(&self) -> Option<u64> {
+        self.limit_requests
+    }
+
+    pub fn get_limit_tokens(&self) -> Option<u64> {
+        self.limit_tokens
+    }
+
+    pub fn get_remaining_requests(&self) -> Option<u64> {
+        self.remaining_requests
+    }
+
+    pub fn get_remaining_tokens(&self) -> Option<u64> {
+        self.remaining_tokens
+    }
+
+    pub fn get_reset_requests(&self) -> Option<Duration> {
+        self.reset_requests
+    }
+
+    pub fn get_reset_tokens(&self) -> Option<Duration> {
+        self.reset_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_from_headers() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            ("x-request-id", "req_123"),
+            ("openai-processing-ms", "250"),
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "499"),
+            ("x-ratelimit-remaining-tokens", "29000"),
+            ("x-ratelimit-reset-requests", "120ms"),
+            ("x-ratelimit-reset-tokens", "1m2s"),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+        let metadata = ResponseMetadata::from_headers(StatusCode::OK, &headers);
+        assert_eq!(metadata.get_request_id(), Some("req_123"));
+        assert_eq!(
+            metadata.get_processing_time(),
+            Some(Duration::from_millis(250))
+        );
+
+        let rate_limit = metadata.get_rate_limit();
+        assert_eq!(rate_limit.get_limit_requests(), Some(500));
+        assert_eq!(rate_limit.get_limit_tokens(), None);
+        assert_eq!(rate_limit.get_remaining_requests(), Some(499));
+        assert_eq!(rate_limit.get_remaining_tokens(), Some(29000));
+        assert_eq!(
+            rate_limit.get_reset_requests(),
+            Some(Duration::from_millis(120))
+        );
+        assert_eq!(rate_limit.get_reset_tokens(), Some(Duration::from_secs(62)));
+    }
+
+    #[test]
+    fn test_malformed_headers_dropped() {
+        let huge = format!("{}h", "9".repeat(400));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset-requests", huge.parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "1.2.3s".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "-1".parse().unwrap());
+        headers.insert("openai-processing-ms", "soon".parse().unwrap());
+        let metadata = ResponseMetadata::from_headers(StatusCode::OK, &headers);
+        assert_eq!(metadata.get_processing_time(), None);
+        assert_eq!(metadata.get_rate_limit(), &RateLimit::default());
+    }
+}
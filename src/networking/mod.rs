@@ -1,10 +1,67 @@
+mod azure;
+mod beta;
+mod cassette;
+mod config;
+#[cfg(test)]
+mod fixtures;
+mod key_pool;
+mod logging;
+mod metadata;
+mod multipart;
+mod pagination;
+mod rate_limit;
+mod retry;
+mod stream;
+pub mod transport;
+
+pub use self::azure::AzureConfig;
+pub use self::beta::AssistantsVersion;
+pub(crate) use self::config::{NetworkingConfig, Reply};
+pub use self::key_pool::{KeyPool, KeyStats, PoolStrategy};
+pub use self::metadata::{RateLimit, ResponseMetadata};
+pub use self::multipart::MultipartForm;
+pub use self::rate_limit::RateLimiter;
+pub(crate) use self::retry::retry_after;
+pub use self::retry::RetryPolicy;
+
+#[cfg(feature = "blocking")]
 mod assistant;
+#[cfg(feature = "blocking")]
 mod chat;
+#[cfg(feature = "blocking")]
 mod core;
+#[cfg(feature = "blocking")]
 mod file;
+#[cfg(feature = "blocking")]
 mod message;
+#[cfg(feature = "blocking")]
 mod model;
+#[cfg(feature = "blocking")]
 mod run;
+#[cfg(feature = "blocking")]
 mod thread;
 
-pub use core::Networking;
+#[cfg(feature = "async")]
+mod asynchronous;
+
+#[cfg(feature = "blocking")]
+pub use self::core::Networking;
+#[cfg(feature = "blocking")]
+pub use self::pagination::Paginator;
+#[cfg(feature = "blocking")]
+pub use self::stream::ChatCompletionStream;
+#[cfg(feature = "blocking")]
+pub use self::{
+    assistant::AssistantActions, chat::ChatCompletionActions, file::FileActions,
+    message::MessageActions, model::ModelActions, run::RunActions, thread::ThreadActions,
+};
+
+#[cfg(feature = "async")]
+pub use self::asynchronous::{
+    AsyncAssistantActions, AsyncChatCompletionActions, AsyncFileActions, AsyncMessageActions,
+    AsyncModelActions, AsyncNetworking, AsyncRunActions, AsyncThreadActions,
+};
+#[cfg(feature = "async")]
+pub use self::pagination::AsyncPaginator;
+#[cfg(feature = "async")]
+pub use self::stream::AsyncChatCompletionStream;
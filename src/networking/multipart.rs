@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::types::error::OpenApiError;
+
+/// Multipart payload held as owned parts rather than a `reqwest` form, so that a fresh form can be
+/// produced for every attempt of a request. File parts are reopened on each attempt and streamed
+/// from disk instead of being held in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MultipartForm {
+    parts: Vec<(String, MultipartPart)>,
+}
+
+#[derive(Clone, Debug)]
+enum MultipartPart {
+    Text(String),
+    File(PathBuf),
+}
+
+impl MultipartForm {
+    pub fn new() -> Self {
+        MultipartForm::default()
+    }
+
+    pub fn text<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.parts
+            .push((name.into(), MultipartPart::Text(value.into())));
+        self
+    }
+
+    pub fn file<N: Into<String>, P: Into<PathBuf>>(mut self, name: N, path: P) -> Self {
+        self.parts
+            .push((name.into(), MultipartPart::File(path.into())));
+        self
+    }
+
+    /// Describes the form as JSON, with file parts reduced to their file name, so that requests
+    /// can be compared without reading the files.
+    pub(crate) fn describe(&self) -> Value {
+        let parts = self
+            .parts
+            .iter()
+            .map(|(name, part)| {
+                let value = match part {
+                    MultipartPart::Text(value) => Value::String(value.clone()),
+                    MultipartPart::File(path) => json!({
+                        "file": path.file_name().map(|name| name.to_string_lossy().into_owned())
+                    }),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        Value::Object(parts)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn to_blocking_form(
+        &self,
+    ) -> Result<reqwest::blocking::multipart::Form, OpenApiError> {
+        let mut form = reqwest::blocking::multipart::Form::new();
+        for (name, part) in &self.parts {
+            form = match part {
+                MultipartPart::Text(value) => form.text(name.clone(), value.clone()),
+                MultipartPart::File(path) => form.file(name.clone(), path)?,
+            };
+        }
+        Ok(form)
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn to_async_form(&self) -> Result<reqwest::multipart::Form, OpenApiError> {
+        let mut form = reqwest::multipart::Form::new();
+        for (name, part) in &self.parts {
+            form = match part {
+                MultipartPart::Text(value) => form.text(name.clone(), value.clone()),
+                MultipartPart::File(path) => {
+                    // The async form has no file helper, so the file is wrapped as a stream
+                    let file_name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let file = tokio::fs::File::open(path).await?;
+                    let length = file.metadata().await?.len();
+                    let body = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(file));
+                    let part = reqwest::multipart::Part::stream_with_length(body, length)
+                        .file_name(file_name);
+                    form.part(name.clone(), part)
+                }
+            };
+        }
+        Ok(form)
+    }
+}
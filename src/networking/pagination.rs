@@ -0,0 +1,203 @@
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+
+use crate::types::common::{ApiList, Identifiable, ListParams};
+use crate::types::error::OpenApiError;
+
+/// The largest page the list endpoints accept, used to keep the number of requests down.
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[cfg(feature = "blocking")]
+type PageFetch<'a, T> = Box<dyn FnMut(&ListParams) -> Result<ApiList<T>, OpenApiError> + 'a>;
+
+#[cfg(feature = "async")]
+type PageFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<ApiList<T>, OpenApiError>> + Send + 'a>>;
+
+/// `Paginator` lazily iterates over every object of a list endpoint, requesting the next page
+/// with the `last_id` of the previous one as the `after` cursor for as long as `has_more` is set.
+/// Iteration stops after yielding the first error.
+///
+/// # Examples
+/// ```ignore
+/// for message in client.iter_messages(&thread) {
+///     println!("{:?}", message?);
+/// }
+/// ```
+#[cfg(feature = "blocking")]
+pub struct Paginator<'a, T> {
+    fetch: PageFetch<'a, T>,
+    cursor: Cursor<T>,
+}
+
+/// `AsyncPaginator` is the async counterpart of `Paginator`, yielding objects from `next`.
+///
+/// # Examples
+/// ```ignore
+/// let mut messages = client.iter_messages(&thread);
+/// while let Some(message) = messages.next().await {
+///     println!("{:?}", message?);
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub struct AsyncPaginator<'a, T> {
+    fetch: Box<dyn FnMut(ListParams) -> PageFuture<'a, T> + Send + 'a>,
+    cursor: Cursor<T>,
+}
+
+/// The position of a paginator, shared by the blocking and async variants.
+struct Cursor<T> {
+    params: ListParams,
+    page: std::vec::IntoIter<T>,
+    finished: bool,
+}
+
+impl<T: Identifiable> Cursor<T> {
+    fn new() -> Self {
+        Self {
+            params: ListParams::new()
+                .with_limit(MAX_PAGE_SIZE)
+                .unwrap_or_default(),
+            page: Vec::new().into_iter(),
+            finished: false,
+        }
+    }
+
+    /// Buffers a fetched page and moves the cursor past it, returning the error of a failed fetch.
+    fn receive(&mut self, result: Result<ApiList<T>, OpenApiError>) -> Option<OpenApiError> {
+        let page = match result {
+            Ok(page) => page,
+            Err(err) => {
+                self.finished = true;
+                return Some(err);
+            }
+        };
+        // Not every list returns `last_id`, in which case the last object is the cursor
+        let next = page
+            .get_last_id()
+            .map(String::from)
+            .or_else(|| page.get_data_vec().last().map(T::get_identifier));
+        match next {
+            Some(next) if page.has_more() && self.params.get_after() != Some(next.as_str()) => {
+                self.params = self.params.clone().with_after(next);
+            }
+            _ => self.finished = true,
+        }
+        self.page = page.into_iter();
+        None
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<'a, T: Identifiable> Paginator<'a, T> {
+    pub(crate) fn new<F>(fetch: F) -> Self
+    where
+        F: FnMut(&ListParams) -> Result<ApiList<T>, OpenApiError> + 'a,
+    {
+        Self {
+            fetch: Box::new(fetch),
+            cursor: Cursor::new(),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<T: Identifiable> Iterator for Paginator<'_, T> {
+    type Item = Result<T, OpenApiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.cursor.page.next() {
+                return Some(Ok(item));
+            }
+            if self.cursor.finished {
+                return None;
+            }
+            let page = (self.fetch)(&self.cursor.params);
+            if let Some(err) = self.cursor.receive(page) {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T: Identifiable> AsyncPaginator<'a, T> {
+    pub(crate) fn new<F>(fetch: F) -> Self
+    where
+        F: FnMut(ListParams) -> PageFuture<'a, T> + Send + 'a,
+    {
+        Self {
+            fetch: Box::new(fetch),
+            cursor: Cursor::new(),
+        }
+    }
+
+    /// Returns the next object, fetching the next page when the current one is exhausted.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<T, OpenApiError>> {
+        loop {
+            if let Some(item) = self.cursor.page.next() {
+                return Some(Ok(item));
+            }
+            if self.cursor.finished {
+                return None;
+            }
+            let page = (self.fetch)(self.cursor.params.clone()).await;
+            if let Some(err) = self.cursor.receive(page) {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn page(ids: &[&str], has_more: bool) -> ApiList<String> {
+        serde_json::from_value(json!({
+            "object": "list",
+            "data": ids,
+            "first_id": ids.first(),
+            "last_id": ids.last(),
+            "has_more": has_more,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_follows_cursor_across_pages() {
+        let mut cursors = Vec::new();
+        let items: Vec<String> = Paginator::new(|params: &ListParams| {
+            cursors.push(params.get_after().map(String::from));
+            match params.get_after() {
+                None => Ok(page(&["msg_1", "msg_2"], true)),
+                Some("msg_2") => Ok(page(&["msg_3"], false)),
+                Some(other) => panic!("unexpected cursor {}", other),
+            }
+        })
+        .collect::<Result<_, _>>()
+        .unwrap();
+        assert_eq!(items, ["msg_1", "msg_2", "msg_3"]);
+        assert_eq!(cursors, [None, Some(String::from("msg_2"))]);
+    }
+
+    #[test]
+    fn test_stops_after_error() {
+        let mut calls = 0;
+        let mut paginator = Paginator::new(|_: &ListParams| {
+            calls += 1;
+            match calls {
+                1 => Ok(page(&["msg_1"], true)),
+                _ => Err(OpenApiError::ClientError(String::from("connection reset"))),
+            }
+        });
+        assert!(paginator.next().unwrap().is_ok());
+        assert!(paginator.next().unwrap().is_err());
+        assert!(paginator.next().is_none());
+    }
+}
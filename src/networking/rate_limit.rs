@@ -0,0 +1,341 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::types::common::Usage;
+
+/// Average characters per token assumed until usage reported by the api is available.
+const DEFAULT_CHARS_PER_TOKEN: f64 = 4.0;
+
+/// Tokens added per message for the role and formatting the api wraps each message in.
+const TOKENS_PER_MESSAGE: f64 = 4.0;
+
+/// Weight given to each new observation when recalibrating the characters per token.
+const CALIBRATION_WEIGHT: f64 = 0.2;
+
+/// `RateLimiter` is a client side token bucket limiter, pacing requests before they are sent
+/// rather than waiting for the api to respond with a 429. Clones share the same buckets, so a
+/// single limiter can pace every client in a process sharing an organization key.
+///
+/// Tokens for chat completions are estimated from the characters of the messages plus
+/// `max_tokens`, and the estimate is corrected from the `Usage` of each completion. Calls wait
+/// (blocking the thread, or yielding for the async client) until the buckets allow them.
+///
+/// Streamed completions are always charged their estimate, even with `include_usage`, as their
+/// usage only arrives with the last chunk. They do not recalibrate the estimate either.
+///
+/// # Fields
+///
+/// * `state: Arc<Mutex<LimiterState>>` - The buckets and calibration, shared between clones.
+///
+/// # Examples
+/// ```ignore
+/// let limiter = RateLimiter::new()
+///     .with_requests_per_minute(500)
+///     .with_tokens_per_minute(30_000);
+/// let client = OpenAIClient::new(apikey, None)?.with_rate_limiter(limiter.clone());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    chars_per_token: f64,
+}
+
+impl Default for LimiterState {
+    fn default() -> Self {
+        Self {
+            requests: None,
+            tokens: None,
+            chars_per_token: DEFAULT_CHARS_PER_TOKEN,
+        }
+    }
+}
+
+/// A bucket refilled continuously up to `capacity` per minute. Taking more than is available
+/// leaves the bucket in debt, which queues later callers behind earlier ones.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            capacity: per_minute.max(1) as f64,
+            available: per_minute.max(1) as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill_per_second(&self) -> f64 {
+        self.capacity / 60.0
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_second()).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Takes `amount` and returns how long the caller must wait for the bucket to cover it,
+    /// along with the amount actually taken.
+    fn take(&mut self, amount: f64, now: Instant) -> (Duration, f64) {
+        self.refill(now);
+        // A single request larger than the bucket would otherwise never be allowed through
+        let taken = amount.min(self.capacity);
+        self.available -= taken;
+        let delay = match self.available < 0.0 {
+            true => Duration::from_secs_f64(-self.available / self.refill_per_second()),
+            false => Duration::ZERO,
+        };
+        (delay, taken)
+    }
+
+    fn give(&mut self, amount: f64) {
+        self.available = (self.available + amount).min(self.capacity);
+    }
+}
+
+/// Permission to send one attempt of a request, returned by `RateLimiter::acquire`.
+#[derive(Debug)]
+pub(crate) struct Permit {
+    limiter: RateLimiter,
+    delay: Duration,
+    estimated_tokens: f64,
+    charged_tokens: f64,
+    prompt_chars: f64,
+}
+
+impl RateLimiter {
+    /// A limiter without any limits, see `with_requests_per_minute` and `with_tokens_per_minute`.
+    pub fn new() -> Self {
+        RateLimiter::default()
+    }
+
+    pub fn with_requests_per_minute(self, requests_per_minute: u32) -> Self {
+        self.state.lock().unwrap().requests = Some(Bucket::new(requests_per_minute));
+        self
+    }
+
+    pub fn with_tokens_per_minute(self, tokens_per_minute: u32) -> Self {
+        self.state.lock().unwrap().tokens = Some(Bucket::new(tokens_per_minute));
+        self
+    }
+
+    /// Returns the current estimate of characters per token, recalibrated from reported usage.
+    pub fn get_chars_per_token(&self) -> f64 {
+        self.state.lock().unwrap().chars_per_token
+    }
+
+    /// Reserves capacity for a request, where `chat_body` is the payload of a chat completion
+    /// whose tokens should be counted. The returned permit holds the delay to wait before sending.
+    pub(crate) fn acquire(&self, chat_body: Option<&Value>) -> Permit {
+        self.acquire_at(chat_body, Instant::now())
+    }
+
+    fn acquire_at(&self, chat_body: Option<&Value>, now: Instant) -> Permit {
+        let mut state = self.state.lock().unwrap();
+        let (prompt_chars, messages, max_tokens) = chat_body.map(prompt_size).unwrap_or_default();
+        let estimated_tokens = match chat_body {
+            Some(_) => {
+                (prompt_chars / state.chars_per_token + messages * TOKENS_PER_MESSAGE + max_tokens)
+                    .ceil()
+            }
+            None => 0.0,
+        };
+
+        let request_delay = state
+            .requests
+            .as_mut()
+            .map(|bucket| bucket.take(1.0, now).0)
+            .unwrap_or_default();
+        let (token_delay, charged_tokens) = match (&mut state.tokens, estimated_tokens > 0.0) {
+            (Some(bucket), true) => bucket.take(estimated_tokens, now),
+            _ => (Duration::ZERO, 0.0),
+        };
+        Permit {
+            limiter: self.clone(),
+            delay: request_delay.max(token_delay),
+            estimated_tokens,
+            charged_tokens,
+            prompt_chars,
+        }
+    }
+}
+
+impl Permit {
+    pub(crate) fn get_delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Corrects the token bucket once the attempt completes. Failed attempts are refunded what
+    /// was taken from the bucket, successful ones are charged the `usage` reported by the api.
+    /// Responses without usage, such as streams, keep the charge taken up front.
+    pub(crate) fn settle(&self, response: Option<&Value>) {
+        if self.estimated_tokens <= 0.0 {
+            return;
+        }
+        let usage = response
+            .and_then(|r| r.get("usage"))
+            .and_then(|u| serde_json::from_value::<Usage>(u.clone()).ok());
+        let mut state = self.limiter.state.lock().unwrap();
+        let used = match (response, &usage) {
+            (None, _) => 0.0,
+            (Some(_), Some(usage)) => usage.get_total_tokens() as f64,
+            (Some(_), None) => self.charged_tokens,
+        };
+        if let Some(bucket) = state.tokens.as_mut() {
+            bucket.give(self.charged_tokens - used);
+        }
+        if let Some(usage) = usage.filter(|u| u.get_prompt_tokens() > 0) {
+            let observed = self.prompt_chars / usage.get_prompt_tokens() as f64;
+            state.chars_per_token =
+                state.chars_per_token * (1.0 - CALIBRATION_WEIGHT) + observed * CALIBRATION_WEIGHT;
+        }
+    }
+}
+
+/// Returns the characters across all messages, the number of messages and `max_tokens`.
+fn prompt_size(body: &Value) -> (f64, f64, f64) {
+    fn chars(value: &Value) -> usize {
+        match value {
+            Value::String(text) => text.chars().count(),
+            Value::Array(values) => values.iter().map(chars).sum(),
+            // Images are not counted, as an inlined image would count as megabytes of text
+            Value::Object(map) => map
+                .iter()
+                .filter(|(key, _)| *key != "image_url")
+                .map(|(_, value)| chars(value))
+                .sum(),
+            _ => 0,
+        }
+    }
+    let messages = body
+        .get("messages")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let prompt_chars = messages.iter().map(chars).sum::<usize>() as f64;
+    let max_tokens = body
+        .get("max_tokens")
+        .and_then(Value::as_f64)
+        .unwrap_or_default();
+    (prompt_chars, messages.len() as f64, max_tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chat_body(content: &str, max_tokens: u32) -> Value {
+        json!({"model": "gpt-4", "messages": [{"role": "user", "content": content}], "max_tokens": max_tokens})
+    }
+
+    #[test]
+    fn test_requests_queue_once_bucket_is_empty() {
+        let limiter = RateLimiter::new().with_requests_per_minute(2);
+        let now = Instant::now();
+        assert_eq!(limiter.acquire_at(None, now).get_delay(), Duration::ZERO);
+        assert_eq!(limiter.acquire_at(None, now).get_delay(), Duration::ZERO);
+        // Refills at one request every 30 seconds, later callers queue behind earlier ones
+        assert_eq!(
+            limiter.acquire_at(None, now).get_delay(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            limiter.acquire_at(None, now).get_delay(),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn test_tokens_estimated_and_settled_from_usage() {
+        let limiter = RateLimiter::new().with_tokens_per_minute(600);
+        let now = Instant::now();
+        // 400 characters including the role at 4 per token, 4 for the message and 100 for max_tokens
+        let body = chat_body(&"a".repeat(396), 100);
+        let permit = limiter.acquire_at(Some(&body), now);
+        assert_eq!(permit.estimated_tokens, 204.0);
+        assert_eq!(permit.get_delay(), Duration::ZERO);
+
+        let response =
+            json!({"usage": {"prompt_tokens": 50, "completion_tokens": 10, "total_tokens": 60}});
+        permit.settle(Some(&response));
+        let available = limiter
+            .state
+            .lock()
+            .unwrap()
+            .tokens
+            .as_ref()
+            .unwrap()
+            .available;
+        assert!((available - 540.0).abs() < 1.0);
+        // 400 characters over 50 tokens pulls the estimate towards 8 characters per token
+        assert!(limiter.get_chars_per_token() > DEFAULT_CHARS_PER_TOKEN);
+    }
+
+    #[test]
+    fn test_failed_attempt_refunded() {
+        let limiter = RateLimiter::new().with_tokens_per_minute(600);
+        let permit = limiter.acquire_at(Some(&chat_body("hello", 500)), Instant::now());
+        permit.settle(None);
+        let available = limiter
+            .state
+            .lock()
+            .unwrap()
+            .tokens
+            .as_ref()
+            .unwrap()
+            .available;
+        assert!((available - 600.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_oversized_attempt_refunds_only_what_was_taken() {
+        let limiter = RateLimiter::new().with_tokens_per_minute(600);
+        let now = Instant::now();
+        limiter.acquire_at(Some(&chat_body("hello", 100)), now);
+        let drained = limiter
+            .state
+            .lock()
+            .unwrap()
+            .tokens
+            .as_ref()
+            .unwrap()
+            .available;
+
+        // Estimated far above the capacity, so only the capacity is taken
+        let oversized = limiter.acquire_at(Some(&chat_body("hello", 5_000)), now);
+        assert_eq!(oversized.charged_tokens, 600.0);
+        oversized.settle(None);
+        let available = limiter
+            .state
+            .lock()
+            .unwrap()
+            .tokens
+            .as_ref()
+            .unwrap()
+            .available;
+        assert!((available - drained).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_inlined_images_not_counted() {
+        let url = format!("data:image/png;base64,{}", "A".repeat(10_000));
+        let body = json!({"messages": [{"role": "user", "content": [
+            {"type": "image_url", "image_url": {"url": url}},
+        ]}]});
+        // Only the role and the type of the part are counted
+        assert_eq!(prompt_size(&body), (13.0, 1.0, 0.0));
+    }
+}
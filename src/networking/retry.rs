@@ -0,0 +1,386 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::types::error::{ApiErrorCode, OpenApiError};
+
+/// Statuses considered transient, any other error status is returned immediately.
+const RETRYABLE_STATUSES: [StatusCode; 6] = [
+    StatusCode::REQUEST_TIMEOUT,
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
+/// `RetryPolicy` controls how `Networking` retries transient failures (429s, 5xx responses and
+/// connection errors). Timeouts and other failures after a request was sent are only retried for
+/// idempotent methods, as a `POST` may already have been processed. Delays grow exponentially from `initial_backoff` up to `max_backoff` with
+/// jitter applied, unless the response specifies its own delay through `Retry-After` or the
+/// `x-ratelimit-reset-*` headers. Delays requested by the server are capped at `max_backoff` too.
+///
+/// # Fields
+///
+/// * `max_attempts: u32` - The maximum number of attempts made for a request, including the first.
+/// * `initial_backoff: Duration` - The delay before the first retry.
+/// * `max_backoff: Duration` - The upper bound of any delay, computed or requested by the server.
+/// * `multiplier: f64` - The factor the delay grows by after every attempt.
+/// * `jitter: bool` - Whether to randomise delays, avoiding many clients retrying in lockstep.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// A policy making a single attempt, i.e. never retrying.
+    pub fn none() -> Self {
+        RetryPolicy::new(1)
+    }
+
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Result<Self, OpenApiError> {
+        if multiplier < 1.0 {
+            return Err(OpenApiError::RestrictedValue(
+                "Retry multiplier must be at least 1.0".into(),
+            ));
+        }
+        self.multiplier = multiplier;
+        Ok(self)
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns the delay to wait before making attempt `attempt + 1` of a `method` request after
+    /// `err`, or `None` when the error is not transient or the attempts are exhausted.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        method: &Method,
+        err: &OpenApiError,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        match err {
+            OpenApiError::Api(api_err)
+                if RETRYABLE_STATUSES.contains(&api_err.get_status())
+                    && api_err.get_code() != &ApiErrorCode::InsufficientQuota =>
+            {
+                Some(match api_err.get_retry_after() {
+                    Some(retry_after) => retry_after.min(self.max_backoff),
+                    None => self.backoff(attempt),
+                })
+            }
+            OpenApiError::ReqwestError(e) if e.is_connect() => Some(self.backoff(attempt)),
+            OpenApiError::ReqwestError(e)
+                if (e.is_timeout() || e.is_request()) && method.is_idempotent() =>
+            {
+                Some(self.backoff(attempt))
+            }
+            _ => None,
+        }
+    }
+
+    /// Exponential backoff for the retry following `attempt`, with equal jitter applied.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        let backoff = match self.jitter {
+            true => backoff / 2.0 + backoff / 2.0 * random_fraction(),
+            false => backoff,
+        };
+        Duration::from_secs_f64(backoff)
+    }
+}
+
+/// Pseudo random value in `[0, 1]`, sufficient for spreading retries without pulling in `rand`.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.finish() as f64 / u64::MAX as f64
+}
+
+/// Reads the delay requested by the server from the `retry-after-ms`, `retry-after` and
+/// `x-ratelimit-reset-*` headers, in that order of preference. When several rate limits are
+/// reported, the reset of the exhausted limit is used, otherwise the longest reset.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let seconds = |name: &str, scale: f64| {
+        header(name)
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .and_then(|v| duration_from_secs(v / scale))
+    };
+    if let Some(delay) = seconds("retry-after-ms", 1000.0) {
+        return Some(delay);
+    }
+    if let Some(delay) = seconds("retry-after", 1.0) {
+        return Some(delay);
+    }
+
+    let resets: Vec<(bool, Duration)> = ["requests", "tokens"]
+        .iter()
+        .filter_map(|limit| {
+            let reset =
+                header(&format!("x-ratelimit-reset-{}", limit)).and_then(parse_reset_duration)?;
+            let exhausted = header(&format!("x-ratelimit-remaining-{}", limit))
+                .map(|v| v.trim() == "0")
+                .unwrap_or(false);
+            Some((exhausted, reset))
+        })
+        .collect();
+    let exhausted = resets.iter().filter(|(e, _)| *e).map(|(_, d)| *d).max();
+    exhausted.or_else(|| resets.iter().map(|(_, d)| *d).max())
+}
+
+/// Parses the duration format used by the `x-ratelimit-reset-*` headers, e.g. `20ms`, `1s`,
+/// `6m0s` or `1h2m3.5s`.
+pub(crate) fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_end] {
+            "h" => number * 3600.0,
+            "m" => number * 60.0,
+            "s" | "" => number,
+            "ms" => number / 1000.0,
+            _ => return None,
+        };
+        total += seconds;
+        rest = &rest[unit_end..];
+    }
+    duration_from_secs(total)
+}
+
+/// Converts seconds read from a header, clamping negative values to zero. Values that are NaN,
+/// infinite or too large for a `Duration` are rejected, as `Duration::from_secs_f64` would panic.
+fn duration_from_secs(seconds: f64) -> Option<Duration> {
+    match seconds.is_nan() {
+        true => None,
+        false => Duration::try_from_secs_f64(seconds.max(0.0)).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: StatusCode, headers: &[(&'static str, &'static str)]) -> OpenApiError {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, value.parse().unwrap());
+        }
+        OpenApiError::from_response(status, &map, b"{}")
+    }
+
+    #[test]
+    fn test_parse_reset_duration() {
+        assert_eq!(
+            parse_reset_duration("20ms"),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(parse_reset_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(
+            parse_reset_duration("1h2m3.5s"),
+            Some(Duration::from_secs_f64(3723.5))
+        );
+        assert_eq!(parse_reset_duration("soon"), None);
+        assert_eq!(parse_reset_duration(&format!("{}h", "9".repeat(400))), None);
+    }
+
+    #[test]
+    fn test_unrepresentable_retry_after_ignored() {
+        for (name, value) in [
+            ("retry-after", "1e300"),
+            ("retry-after", "inf"),
+            ("retry-after", "NaN"),
+            ("retry-after-ms", "inf"),
+        ] {
+            let err = api_error(StatusCode::SERVICE_UNAVAILABLE, &[(name, value)]);
+            let OpenApiError::Api(api_err) = &err else {
+                panic!("unexpected error {:?}", err);
+            };
+            assert_eq!(api_err.get_retry_after(), None, "{}: {}", name, value);
+            let delay =
+                RetryPolicy::default()
+                    .with_jitter(false)
+                    .retry_delay(1, &Method::GET, &err);
+            assert_eq!(delay, Some(Duration::from_millis(500)));
+        }
+        let err = api_error(StatusCode::SERVICE_UNAVAILABLE, &[("retry-after", "-5")]);
+        let delay = RetryPolicy::default().retry_delay(1, &Method::GET, &err);
+        assert_eq!(delay, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_retry_after_prefers_exhausted_limit() {
+        let err = api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            &[
+                ("x-ratelimit-remaining-requests", "10"),
+                ("x-ratelimit-reset-requests", "30s"),
+                ("x-ratelimit-remaining-tokens", "0"),
+                ("x-ratelimit-reset-tokens", "2s"),
+            ],
+        );
+        let delay = RetryPolicy::default().retry_delay(1, &Method::GET, &err);
+        assert_eq!(delay, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let err = api_error(StatusCode::SERVICE_UNAVAILABLE, &[("retry-after", "7")]);
+        let delay = RetryPolicy::default().retry_delay(1, &Method::GET, &err);
+        assert_eq!(delay, Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn test_retry_after_capped_at_max_backoff() {
+        let err = api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            &[
+                ("x-ratelimit-remaining-requests", "0"),
+                ("x-ratelimit-reset-requests", "6m0s"),
+            ],
+        );
+        let policy = RetryPolicy::default().with_max_backoff(Duration::from_secs(10));
+        assert_eq!(
+            policy.retry_delay(1, &Method::GET, &err),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_no_retry_for_client_errors_or_exhausted_attempts() {
+        let policy = RetryPolicy::new(3);
+        let not_found = api_error(StatusCode::NOT_FOUND, &[]);
+        assert_eq!(policy.retry_delay(1, &Method::GET, &not_found), None);
+
+        let unavailable = api_error(StatusCode::SERVICE_UNAVAILABLE, &[]);
+        assert!(policy.retry_delay(2, &Method::GET, &unavailable).is_some());
+        assert_eq!(policy.retry_delay(3, &Method::GET, &unavailable), None);
+        assert_eq!(
+            RetryPolicy::none().retry_delay(1, &Method::GET, &unavailable),
+            None
+        );
+    }
+
+    /// A request error raised once the request was sent, the server closing the connection
+    /// without answering.
+    #[cfg(feature = "blocking")]
+    fn dropped_connection_error(method: Method) -> OpenApiError {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || drop(listener.accept()));
+        let err = reqwest::blocking::Client::new()
+            .request(method, url)
+            .send()
+            .unwrap_err();
+        server.join().unwrap();
+        OpenApiError::from(err)
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn test_request_errors_only_retried_when_idempotent() {
+        let policy = RetryPolicy::default();
+        let err = dropped_connection_error(Method::GET);
+        assert!(matches!(&err, OpenApiError::ReqwestError(e) if e.is_request() && !e.is_connect()));
+        assert!(policy.retry_delay(1, &Method::GET, &err).is_some());
+
+        let err = dropped_connection_error(Method::POST);
+        assert_eq!(policy.retry_delay(1, &Method::POST, &err), None);
+
+        // Nothing reached the server, so even a POST is safe to repeat
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let err = reqwest::blocking::Client::new()
+            .post(format!("http://127.0.0.1:{}", port))
+            .send()
+            .unwrap_err();
+        assert!(err.is_connect());
+        let err = OpenApiError::from(err);
+        assert!(policy.retry_delay(1, &Method::POST, &err).is_some());
+    }
+
+    #[test]
+    fn test_backoff_is_bounded() {
+        let policy = RetryPolicy::new(10)
+            .with_initial_backoff(Duration::from_secs(1))
+            .with_max_backoff(Duration::from_secs(5));
+        for attempt in 1..10 {
+            let delay = policy.backoff(attempt);
+            assert!(delay <= Duration::from_secs(5));
+        }
+        let policy = policy.with_jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+    }
+}
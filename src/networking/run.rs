@@ -1,4 +1,4 @@
-use crate::common::ApiList;
+use crate::common::{ApiList, ListParams};
 use crate::error::OpenApiError;
 use crate::networking::Networking;
 use crate::run::{Run, RunStep};
@@ -18,11 +18,16 @@ pub trait RunActions {
         run_id: String,
         step_id: String,
     ) -> Result<RunStep, OpenApiError>;
-    fn list_runs(&self, thread_id: String) -> Result<ApiList<Run>, OpenApiError>;
+    fn list_runs(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Run>, OpenApiError>;
     fn list_run_steps(
         &self,
         thread_id: String,
         run_id: String,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<RunStep>, OpenApiError>;
     fn cancel_run(&self, thread_id: String, run_id: String) -> Result<Run, OpenApiError>;
 }
@@ -68,10 +73,14 @@ impl RunActions for Networking {
         )
     }
 
-    fn list_runs(&self, thread_id: String) -> Result<ApiList<Run>, OpenApiError> {
+    fn list_runs(
+        &self,
+        thread_id: String,
+        params: Option<&ListParams>,
+    ) -> Result<ApiList<Run>, OpenApiError> {
         self.send_and_convert(
             Method::GET,
-            format!("threads/{0}/runs", thread_id),
+            ListParams::apply(format!("threads/{0}/runs", thread_id), params)?,
             None,
             None,
         )
@@ -81,10 +90,14 @@ impl RunActions for Networking {
         &self,
         thread_id: String,
         run_id: String,
+        params: Option<&ListParams>,
     ) -> Result<ApiList<RunStep>, OpenApiError> {
         self.send_and_convert(
             Method::GET,
-            format!("threads/{0}/runs/{1}/steps", thread_id, run_id),
+            ListParams::apply(
+                format!("threads/{0}/runs/{1}/steps", thread_id, run_id),
+                params,
+            )?,
             None,
             None,
         )
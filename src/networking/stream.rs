@@ -0,0 +1,356 @@
+//! Incremental decoding of streamed chat completions.
+//!
+//! With `stream` set the api answers with a `text/event-stream`, where every event carries a
+//! `ChatCompletionChunk` as JSON in its `data` field and the stream is terminated by a `[DONE]`
+//! event. An error occurring after the response has started is sent as an event holding the usual
+//! `{"error": {...}}` envelope.
+
+use std::collections::VecDeque;
+#[cfg(feature = "blocking")]
+use std::io::Read;
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+#[cfg(feature = "blocking")]
+use crate::networking::transport::StreamingResponse;
+#[cfg(feature = "async")]
+use crate::networking::transport::{AsyncBody, AsyncStreamingResponse};
+use crate::types::chat::ChatCompletionChunk;
+use crate::types::error::OpenApiError;
+
+/// The `data` of the event terminating a stream.
+const DONE: &str = "[DONE]";
+
+/// Splits a `text/event-stream` body into the `data` of its events, buffering partial lines
+/// across reads.
+#[derive(Debug, Default)]
+struct EventParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+/// The payload of a single server sent event.
+#[derive(Debug, PartialEq)]
+struct Event {
+    event: Option<String>,
+    data: String,
+}
+
+impl EventParser {
+    /// Feeds the next bytes of the body, returning every event completed by them.
+    fn feed(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(event) = self.line(line.trim_end_matches(['\n', '\r'])) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Flushes the last event once the body is complete, in case it lacks a trailing blank line.
+    fn finish(&mut self) -> Option<Event> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        let last = match rest.trim_end_matches(['\n', '\r']) {
+            "" => None,
+            line => self.line(line),
+        };
+        last.or_else(|| self.line(""))
+    }
+
+    fn line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            if self.data.is_empty() {
+                self.event = None;
+                return None;
+            }
+            return Some(Event {
+                event: self.event.take(),
+                data: std::mem::take(&mut self.data).join("\n"),
+            });
+        }
+        // Lines starting with a colon are comments, e.g. keep alive pings
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// The decoding state shared by `ChatCompletionStream` and `AsyncChatCompletionStream`.
+struct ChunkDecoder {
+    status: StatusCode,
+    headers: HeaderMap,
+    parser: EventParser,
+    pending: VecDeque<Event>,
+    finished: bool,
+}
+
+impl ChunkDecoder {
+    fn new(status: StatusCode, headers: HeaderMap) -> Self {
+        Self {
+            status,
+            headers,
+            parser: EventParser::default(),
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    fn receive(&mut self, bytes: &[u8]) {
+        self.pending.extend(self.parser.feed(bytes));
+    }
+
+    /// Handles the end of the body, which is an error unless `[DONE]` has been received.
+    fn end(&mut self) -> Option<Result<ChatCompletionChunk, OpenApiError>> {
+        match self.parser.finish() {
+            Some(event) => {
+                self.pending.push_back(event);
+                None
+            }
+            None => {
+                self.finished = true;
+                Some(Err(OpenApiError::ClientError(
+                    "Chat completion stream ended before [DONE]".into(),
+                )))
+            }
+        }
+    }
+
+    /// Decodes the next buffered event, returning `None` when more of the body is needed.
+    fn next_chunk(&mut self) -> Option<Option<Result<ChatCompletionChunk, OpenApiError>>> {
+        let event = self.pending.pop_front()?;
+        let result = self.decode(event);
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        Some(result)
+    }
+
+    fn decode(&self, event: Event) -> Option<Result<ChatCompletionChunk, OpenApiError>> {
+        if event.data.trim() == DONE {
+            return None;
+        }
+        let value: Value = match serde_json::from_str(&event.data) {
+            Ok(value) => value,
+            Err(err) => return Some(Err(err.into())),
+        };
+        if value.get("error").is_some() || event.event.as_deref() == Some("error") {
+            return Some(Err(OpenApiError::from_response(
+                self.status,
+                &self.headers,
+                event.data.as_bytes(),
+            )));
+        }
+        Some(serde_json::from_value(value).map_err(OpenApiError::from))
+    }
+}
+
+/// `ChatCompletionStream` yields the `ChatCompletionChunk`s of a streamed chat completion as they
+/// arrive, ending after `[DONE]`. Iteration stops after yielding the first error, including an
+/// error event sent mid-stream.
+///
+/// # Examples
+/// ```ignore
+/// let builder = ChatCompletionBuilder::new("gpt-4", messages).with_include_usage(true);
+/// for chunk in builder.build_stream(client.netref())? {
+///     print!("{}", chunk?.get_delta_content().unwrap_or_default());
+/// }
+/// ```
+#[cfg(feature = "blocking")]
+pub struct ChatCompletionStream {
+    body: Box<dyn Read + Send>,
+    decoder: ChunkDecoder,
+}
+
+#[cfg(feature = "blocking")]
+impl ChatCompletionStream {
+    pub(crate) fn new(response: StreamingResponse) -> Self {
+        Self {
+            body: response.body,
+            decoder: ChunkDecoder::new(response.status, response.headers),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Iterator for ChatCompletionStream {
+    type Item = Result<ChatCompletionChunk, OpenApiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer = [0; 4096];
+        loop {
+            if self.decoder.finished {
+                return None;
+            }
+            if let Some(chunk) = self.decoder.next_chunk() {
+                return chunk;
+            }
+            match self.body.read(&mut buffer) {
+                Ok(0) => {
+                    if let Some(err) = self.decoder.end() {
+                        return Some(err);
+                    }
+                }
+                Ok(read) => self.decoder.receive(&buffer[..read]),
+                Err(err) => {
+                    self.decoder.finished = true;
+                    return Some(Err(err.into()));
+                }
+            }
+        }
+    }
+}
+
+/// `AsyncChatCompletionStream` is the async counterpart of `ChatCompletionStream`, yielding
+/// chunks from `next`.
+///
+/// # Examples
+/// ```ignore
+/// let mut stream = builder.build_stream_async(client.netref()).await?;
+/// while let Some(chunk) = stream.next().await {
+///     print!("{}", chunk?.get_delta_content().unwrap_or_default());
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub struct AsyncChatCompletionStream {
+    body: Box<dyn AsyncBody>,
+    decoder: ChunkDecoder,
+}
+
+#[cfg(feature = "async")]
+impl AsyncChatCompletionStream {
+    pub(crate) fn new(response: AsyncStreamingResponse) -> Self {
+        Self {
+            body: response.body,
+            decoder: ChunkDecoder::new(response.status, response.headers),
+        }
+    }
+
+    /// Returns the next chunk, reading more of the body when none is buffered.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<ChatCompletionChunk, OpenApiError>> {
+        loop {
+            if self.decoder.finished {
+                return None;
+            }
+            if let Some(chunk) = self.decoder.next_chunk() {
+                return chunk;
+            }
+            match self.body.next_chunk().await {
+                Ok(Some(bytes)) => self.decoder.receive(&bytes),
+                Ok(None) => {
+                    if let Some(err) = self.decoder.end() {
+                        return Some(err);
+                    }
+                }
+                Err(err) => {
+                    self.decoder.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "blocking"))]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Serves `body` a few bytes at a time, so events are split across reads.
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(7);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    fn stream(body: &str) -> ChatCompletionStream {
+        ChatCompletionStream::new(StreamingResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Box::new(Trickle(Cursor::new(body.as_bytes().to_vec()))),
+        })
+    }
+
+    fn chunk(content: &str) -> String {
+        format!(
+            r#"{{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1,"model":"gpt-4","system_fingerprint":null,"choices":[{{"index":0,"delta":{{"content":"{}"}},"logprobs":null,"finish_reason":null}}]}}"#,
+            content
+        )
+    }
+
+    #[test]
+    fn test_parser_handles_comments_and_multiline_data() {
+        let mut parser = EventParser::default();
+        let mut events =
+            parser.feed(b": ping\r\n\r\ndata: first\r\ndata: second\r\n\r\nevent: error\n");
+        events.extend(parser.feed(b"data: {}\n"));
+        events.extend(parser.finish());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data, "first\nsecond");
+        assert_eq!(events[1].event.as_deref(), Some("error"));
+    }
+
+    #[test]
+    fn test_chunks_until_done() {
+        let usage = r#"{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1,"model":"gpt-4","system_fingerprint":null,"choices":[],"usage":{"prompt_tokens":5,"completion_tokens":2,"total_tokens":7}}"#;
+        let body = format!(
+            "data: {}\n\ndata: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+            chunk("Hel"),
+            chunk("lo"),
+            usage
+        );
+        let chunks: Vec<ChatCompletionChunk> = stream(&body).collect::<Result<_, _>>().unwrap();
+        assert_eq!(chunks.len(), 3);
+        let content: String = chunks
+            .iter()
+            .filter_map(|c| c.get_delta_content())
+            .collect();
+        assert_eq!(content, "Hello");
+        assert_eq!(chunks[2].get_usage().unwrap().get_total_tokens(), 7);
+    }
+
+    #[test]
+    fn test_error_event_ends_stream() {
+        let body = format!(
+            "data: {}\n\ndata: {}\n\n",
+            chunk("Hel"),
+            r#"{"error": {"message": "The server had an error", "type": "server_error", "code": null}}"#
+        );
+        let mut stream = stream(&body);
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(OpenApiError::Api(err))) => {
+                assert_eq!(err.get_message(), "The server had an error")
+            }
+            other => panic!("Expected OpenApiError::Api, found {:?}", other),
+        }
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_truncated_stream_is_an_error() {
+        let mut stream = stream(&format!("data: {}\n\n", chunk("Hel")));
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+}
@@ -0,0 +1,402 @@
+//! HTTP transports used by `Networking` and `AsyncNetworking` to exchange requests with the API.
+//!
+//! `ReqwestTransport`/`AsyncReqwestTransport` are used by default. `MockTransport` serves scripted
+//! responses from memory, and `Cassette`/`AsyncCassette` record and replay real exchanges, allowing
+//! code built on this crate to be tested without a network.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+#[cfg(feature = "blocking")]
+use std::io::{Cursor, Read};
+use std::sync::Mutex;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+use serde::Serialize;
+use serde_json::Value;
+
+#[cfg(feature = "async")]
+pub use crate::networking::cassette::AsyncCassette;
+#[cfg(feature = "blocking")]
+pub use crate::networking::cassette::Cassette;
+pub use crate::networking::MultipartForm;
+use crate::types::error::OpenApiError;
+
+/// Payload of a `TransportRequest`.
+#[derive(Clone, Debug)]
+pub enum RequestBody {
+    Json(Value),
+    Multipart(MultipartForm),
+}
+
+/// A request as handed to a transport, with the url, headers and payload fully resolved.
+#[derive(Clone, Debug)]
+pub struct TransportRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<RequestBody>,
+}
+
+impl TransportRequest {
+    /// Returns the JSON payload of the request, if it has one.
+    pub fn json_body(&self) -> Option<&Value> {
+        match &self.body {
+            Some(RequestBody::Json(value)) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A raw response returned by a transport, decoded by the networking layer.
+#[derive(Clone, Debug)]
+pub struct TransportResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    pub fn new(status: StatusCode, headers: HeaderMap, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    /// A response carrying `body` serialized as JSON.
+    pub fn json<T: Serialize>(status: StatusCode, body: &T) -> Result<Self, OpenApiError> {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", "application/json".parse().unwrap());
+        Ok(Self::new(status, headers, serde_json::to_vec(body)?))
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: &str) -> Result<Self, OpenApiError> {
+        let value = value
+            .parse()
+            .map_err(|_| OpenApiError::ClientError(format!("Invalid header value: {}", value)))?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+}
+
+/// A response whose body is read incrementally, e.g. a `text/event-stream` of chat completion
+/// chunks, returned by `Transport::send_streaming`.
+#[cfg(feature = "blocking")]
+pub struct StreamingResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Box<dyn Read + Send>,
+}
+
+#[cfg(feature = "blocking")]
+impl From<TransportResponse> for StreamingResponse {
+    fn from(response: TransportResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(Cursor::new(response.body)),
+        }
+    }
+}
+
+/// The body of an `AsyncStreamingResponse`, yielding chunks of bytes as they arrive.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncBody: Send {
+    /// Returns the next chunk of the body, or `None` once it is complete.
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, OpenApiError>;
+}
+
+/// The async counterpart of `StreamingResponse`, returned by `AsyncTransport::send_streaming`.
+#[cfg(feature = "async")]
+pub struct AsyncStreamingResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Box<dyn AsyncBody>,
+}
+
+/// A body already held in memory, served as a single chunk.
+#[cfg(feature = "async")]
+struct BufferedBody(Option<Vec<u8>>);
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncBody for BufferedBody {
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, OpenApiError> {
+        Ok(self.0.take())
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncBody for reqwest::Response {
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, OpenApiError> {
+        Ok(self.chunk().await?.map(|chunk| chunk.to_vec()))
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<TransportResponse> for AsyncStreamingResponse {
+    fn from(response: TransportResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(BufferedBody(Some(response.body))),
+        }
+    }
+}
+
+/// Transport used by the blocking `Networking`.
+#[cfg(feature = "blocking")]
+pub trait Transport: Debug + Send + Sync {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError>;
+
+    /// Sends a request whose response body is consumed incrementally. By default the whole body
+    /// is read by `send` first, which suits transports serving responses from memory.
+    fn send_streaming(&self, request: TransportRequest) -> Result<StreamingResponse, OpenApiError> {
+        self.send(request).map(StreamingResponse::from)
+    }
+}
+
+/// Transport used by the async `AsyncNetworking`.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncTransport: Debug + Send + Sync {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError>;
+
+    /// Sends a request whose response body is consumed incrementally. By default the whole body
+    /// is read by `send` first, which suits transports serving responses from memory.
+    async fn send_streaming(
+        &self,
+        request: TransportRequest,
+    ) -> Result<AsyncStreamingResponse, OpenApiError> {
+        self.send(request).await.map(AsyncStreamingResponse::from)
+    }
+}
+
+/// The default blocking transport, backed by `reqwest::blocking::Client`.
+#[cfg(feature = "blocking")]
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "blocking")]
+impl ReqwestTransport {
+    pub fn new(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl ReqwestTransport {
+    fn execute(
+        &self,
+        request: TransportRequest,
+    ) -> Result<reqwest::blocking::Response, OpenApiError> {
+        let mut request_builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+        match request.body {
+            Some(RequestBody::Json(b)) => {
+                request_builder = request_builder
+                    .body(serde_json::to_string(&b)?)
+                    .header("Content-Type", "application/json")
+            }
+            Some(RequestBody::Multipart(mf)) => {
+                request_builder = request_builder.multipart(mf.to_blocking_form()?)
+            }
+            None => {}
+        }
+        Ok(request_builder.send()?)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        let res = self.execute(request)?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes()?.to_vec();
+        Ok(TransportResponse::new(status, headers, body))
+    }
+
+    fn send_streaming(&self, request: TransportRequest) -> Result<StreamingResponse, OpenApiError> {
+        let res = self.execute(request)?;
+        Ok(StreamingResponse {
+            status: res.status(),
+            headers: res.headers().clone(),
+            body: Box::new(res),
+        })
+    }
+}
+
+/// The default async transport, backed by `reqwest::Client`.
+#[cfg(feature = "async")]
+#[derive(Clone, Debug, Default)]
+pub struct AsyncReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "async")]
+impl AsyncReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncReqwestTransport {
+    async fn execute(&self, request: TransportRequest) -> Result<reqwest::Response, OpenApiError> {
+        let mut request_builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+        match request.body {
+            Some(RequestBody::Json(b)) => {
+                request_builder = request_builder
+                    .body(serde_json::to_string(&b)?)
+                    .header("Content-Type", "application/json")
+            }
+            Some(RequestBody::Multipart(mf)) => {
+                request_builder = request_builder.multipart(mf.to_async_form().await?)
+            }
+            None => {}
+        }
+        Ok(request_builder.send().await?)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncTransport for AsyncReqwestTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        let res = self.execute(request).await?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes().await?.to_vec();
+        Ok(TransportResponse::new(status, headers, body))
+    }
+
+    async fn send_streaming(
+        &self,
+        request: TransportRequest,
+    ) -> Result<AsyncStreamingResponse, OpenApiError> {
+        let res = self.execute(request).await?;
+        Ok(AsyncStreamingResponse {
+            status: res.status(),
+            headers: res.headers().clone(),
+            body: Box::new(res),
+        })
+    }
+}
+
+/// In-memory transport serving scripted responses, for testing without a network.
+///
+/// Responses are registered per method and endpoint, where the endpoint is matched against the
+/// end of the request path (e.g. `threads/thread_abc/runs`), so the same script works with any
+/// base url. Responses registered for a route are served in order, with the last one repeated
+/// for any further requests. Every request received is recorded and can be inspected with
+/// `requests`.
+///
+/// # Examples
+/// ```ignore
+/// let mock = MockTransport::new().with_json(Method::GET, "models/gpt-4", StatusCode::OK, &model)?;
+/// let client = OpenAIClient::new("sk-test", None)?.with_transport(mock.clone());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MockTransport {
+    state: std::sync::Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    routes: HashMap<(Method, String), VecDeque<TransportResponse>>,
+    requests: Vec<TransportRequest>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        MockTransport::default()
+    }
+
+    /// Queues `response` for requests with `method` to `endpoint`.
+    pub fn with_response<E: Into<String>>(
+        self,
+        method: Method,
+        endpoint: E,
+        response: TransportResponse,
+    ) -> Self {
+        let endpoint = endpoint.into().trim_matches('/').to_string();
+        self.state
+            .lock()
+            .unwrap()
+            .routes
+            .entry((method, endpoint))
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Queues a JSON response for requests with `method` to `endpoint`.
+    pub fn with_json<E: Into<String>, T: Serialize>(
+        self,
+        method: Method,
+        endpoint: E,
+        status: StatusCode,
+        body: &T,
+    ) -> Result<Self, OpenApiError> {
+        let response = TransportResponse::json(status, body)?;
+        Ok(self.with_response(method, endpoint, response))
+    }
+
+    /// Returns every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    fn respond(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        let mut state = self.state.lock().unwrap();
+        let path = request.url.path().trim_end_matches('/').to_string();
+        let method = request.method.clone();
+        state.requests.push(request);
+
+        // Prefer the longest matching endpoint, so `threads/runs` wins over `runs`
+        let route = state
+            .routes
+            .keys()
+            .filter(|(m, endpoint)| {
+                *m == method && (path == *endpoint || path.ends_with(&format!("/{}", endpoint)))
+            })
+            .max_by_key(|(_, endpoint)| endpoint.len())
+            .cloned();
+        let responses = route.and_then(|route| state.routes.get_mut(&route));
+        match responses {
+            Some(responses) if responses.len() > 1 => Ok(responses.pop_front().unwrap()),
+            Some(responses) if !responses.is_empty() => Ok(responses[0].clone()),
+            _ => Err(OpenApiError::ClientError(format!(
+                "MockTransport has no response for {} {}",
+                method, path
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Transport for MockTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        self.respond(request)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncTransport for MockTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, OpenApiError> {
+        self.respond(request)
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client_builder::OpenAIClientBuilder;
+use crate::credentials::ApiKey;
+use crate::types::error::OpenApiError;
+
+/// Environment variable overriding the location of the config file.
+const CONFIG_PATH_ENV: &str = "RUST_OPEN_AI_CONFIG";
+
+/// `Config` holds the named profiles of a TOML config file, by default read from
+/// `~/.config/rust_open_ai/config.toml` (`%APPDATA%\rust_open_ai\config.toml` on Windows), or
+/// the path in `RUST_OPEN_AI_CONFIG`.
+///
+/// # Examples
+/// ```toml
+/// [profiles.staging]
+/// key_command = "op read op://ci/openai-staging/credential"
+/// organization_id = "org-abc123"
+/// project_id = "proj_abc123"
+/// base_url = "https://gateway.staging.internal/v1"
+/// default_model = "gpt-4o-mini"
+/// connect_timeout_secs = 5
+/// timeout_secs = 60
+/// beta = ["assistants=v2"]
+/// ```
+///
+/// # Fields
+///
+/// * `profiles: HashMap<String, Profile>` - The profiles keyed by name.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+/// `Profile` holds the settings of a single named profile, see `Config`.
+///
+/// # Fields
+///
+/// * `apikey: Option<ApiKey>` - The api key, never serialized. Prefer `key_command` to avoid storing the key in plain text.
+/// * `key_command: Option<String>` - A shell command printing the api key, e.g. a password manager lookup.
+/// * `organization_id: Option<String>` - The organization sent as `OpenAI-Organization`.
+/// * `project_id: Option<String>` - The project sent as `OpenAI-Project`.
+/// * `base_url: Option<String>` - The base url of the api.
+/// * `default_model: Option<String>` - The model used by default, see `get_default_model` on the client.
+/// * `connect_timeout_secs: Option<u64>` - The timeout for establishing a connection.
+/// * `timeout_secs: Option<u64>` - The timeout for a whole request.
+/// * `beta: Vec<String>` - The beta features sent as `OpenAI-Beta`, e.g. `assistants=v2`.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(skip_serializing)]
+    apikey: Option<ApiKey>,
+    key_command: Option<String>,
+    organization_id: Option<String>,
+    project_id: Option<String>,
+    base_url: Option<String>,
+    default_model: Option<String>,
+    connect_timeout_secs: Option<u64>,
+    timeout_secs: Option<u64>,
+    #[serde(default)]
+    beta: Vec<String>,
+}
+
+impl Config {
+    /// Returns the path of the config file, see `Config`.
+    pub fn default_path() -> Result<PathBuf, OpenApiError> {
+        if let Some(path) = env::var_os(CONFIG_PATH_ENV).filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+        let config_dir = match env::var_os("XDG_CONFIG_HOME").filter(|p| !p.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None if cfg!(windows) => env::var_os("APPDATA")
+                .map(PathBuf::from)
+                .ok_or_else(|| OpenApiError::ClientError(String::from("APPDATA is not set")))?,
+            None => env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config"))
+                .ok_or_else(|| OpenApiError::ClientError(String::from("HOME is not set")))?,
+        };
+        Ok(config_dir.join("rust_open_ai").join("config.toml"))
+    }
+
+    /// Loads the config file from `default_path`.
+    pub fn load() -> Result<Self, OpenApiError> {
+        Config::load_from(Config::default_path()?)
+    }
+
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self, OpenApiError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            OpenApiError::ClientError(format!("Unable to read {}: {}", path.display(), e))
+        })?;
+        Config::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, OpenApiError> {
+        toml::from_str(contents)
+            .map_err(|e| OpenApiError::ClientError(format!("Invalid config file: {}", e)))
+    }
+
+    pub fn get_profile(&self, name: &str) -> Result<&Profile, OpenApiError> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| OpenApiError::ClientError(format!("Unknown profile {}", name)))
+    }
+}
+
+impl Profile {
+    /// Applies the environment on top of the profile, where `OPENAI_API_KEY`, `OPENAI_ORG_ID`,
+    /// `OPENAI_PROJECT_ID` and `OPENAI_BASE_URL` override the values from the file. Variables are
+    /// read through `lookup`, e.g. `|name| std::env::var(name).ok()`, and blank values are ignored.
+    pub fn with_env_overrides<F: Fn(&str) -> Option<String>>(mut self, lookup: F) -> Self {
+        let var = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+        if let Some(apikey) = var("OPENAI_API_KEY") {
+            self.apikey = Some(ApiKey::from(apikey));
+            self.key_command = None;
+        }
+        if let Some(organization_id) = var("OPENAI_ORG_ID") {
+            self.organization_id = Some(organization_id);
+        }
+        if let Some(project_id) = var("OPENAI_PROJECT_ID") {
+            self.project_id = Some(project_id);
+        }
+        if let Some(base_url) = var("OPENAI_BASE_URL") {
+            self.base_url = Some(base_url);
+        }
+        self
+    }
+
+    /// Resolves the api key, running `key_command` when no key is set directly.
+    pub(crate) fn resolve_key(&self) -> Result<Option<ApiKey>, OpenApiError> {
+        match (&self.apikey, &self.key_command) {
+            (Some(apikey), _) => Ok(Some(apikey.clone())),
+            (None, Some(command)) => run_key_command(command).map(|key| Some(ApiKey::from(key))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Converts the profile into a client builder, which can be customised further before
+    /// building the client.
+    pub fn to_builder(&self) -> Result<OpenAIClientBuilder, OpenApiError> {
+        let mut builder = OpenAIClientBuilder::new();
+        if let Some(apikey) = self.resolve_key()? {
+            builder = builder.with_resolved_key(apikey);
+        }
+        if let Some(organization_id) = &self.organization_id {
+            builder = builder.with_organization_id(organization_id);
+        }
+        if let Some(project_id) = &self.project_id {
+            builder = builder.with_project(project_id);
+        }
+        if let Some(base_url) = &self.base_url {
+            builder = builder.with_base_url(base_url);
+        }
+        if let Some(default_model) = &self.default_model {
+            builder = builder.with_default_model(default_model);
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            builder = builder.with_connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.timeout_secs {
+            builder = builder.with_timeout(Duration::from_secs(secs));
+        }
+        if !self.beta.is_empty() {
+            builder = builder.with_header("OpenAI-Beta", self.beta.join(","));
+        }
+        Ok(builder)
+    }
+
+    pub fn get_organization_id(&self) -> Option<&str> {
+        self.organization_id.as_deref()
+    }
+
+    pub fn get_project_id(&self) -> Option<&str> {
+        self.project_id.as_deref()
+    }
+
+    pub fn get_base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    pub fn get_default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+/// Runs `command` through the platform shell and returns its trimmed output.
+fn run_key_command(command: &str) -> Result<String, OpenApiError> {
+    let output = match cfg!(windows) {
+        true => Command::new("cmd").args(["/C", command]).output(),
+        false => Command::new("sh").args(["-c", command]).output(),
+    }?;
+    if !output.status.success() {
+        return Err(OpenApiError::ClientError(format!(
+            "Key command exited with {}",
+            output.status
+        )));
+    }
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if key.is_empty() {
+        return Err(OpenApiError::ClientError(String::from(
+            "Key command printed no key",
+        )));
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [profiles.staging]
+        apikey = "sk-staging"
+        project_id = "proj_abc"
+        base_url = "https://gateway.staging.internal/v1"
+        timeout_secs = 30
+        beta = ["assistants=v2"]
+
+        [profiles.ci]
+        key_command = "echo sk-from-command"
+    "#;
+
+    #[test]
+    fn test_parse_profiles() {
+        let config = Config::parse(CONFIG).unwrap();
+        let staging = config.get_profile("staging").unwrap();
+        assert_eq!(staging.get_project_id(), Some("proj_abc"));
+        assert_eq!(staging.timeout_secs, Some(30));
+        assert_eq!(staging.beta, vec!["assistants=v2"]);
+        assert!(!format!("{:?}", staging).contains("sk-staging"));
+        assert!(config.get_profile("production").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_key_command() {
+        let ci = Config::parse(CONFIG)
+            .unwrap()
+            .get_profile("ci")
+            .unwrap()
+            .clone();
+        let ci = ci.with_env_overrides(|name| match name {
+            "OPENAI_API_KEY" => Some(String::from("sk-from-env")),
+            "OPENAI_ORG_ID" => Some(String::from(" ")),
+            _ => None,
+        });
+        assert!(ci.key_command.is_none());
+        assert_eq!(ci.resolve_key().unwrap().unwrap().expose(), "sk-from-env");
+        assert_eq!(ci.get_organization_id(), None);
+    }
+
+    #[test]
+    fn test_invalid_header_values_rejected() {
+        let config = Config::parse(
+            r#"
+            [profiles.org]
+            apikey = "sk-test"
+            organization_id = "org-abc\nX-Injected: 1"
+
+            [profiles.key]
+            apikey = "sk-test\n"
+        "#,
+        )
+        .unwrap();
+        for name in ["org", "key"] {
+            let builder = config.get_profile(name).unwrap().to_builder().unwrap();
+            let result = builder
+                .with_base_url("http://localhost:8000/v1")
+                .build_config();
+            assert!(matches!(result, Err(OpenApiError::ClientError(_))));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_key_command() {
+        let config = Config::parse(CONFIG).unwrap();
+        let key = config.get_profile("ci").unwrap().resolve_key().unwrap();
+        assert_eq!(key.unwrap().expose(), "sk-from-command");
+    }
+}
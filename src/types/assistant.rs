@@ -1,18 +1,19 @@
 use crate::impl_ref;
 use serde::{Deserialize, Serialize};
-use serde_urlencoded;
 use std::collections::HashMap;
 
-use crate::model::Model;
-use crate::networking::Networking;
-use crate::types::common::{Identifiable, Tools};
+#[cfg(feature = "blocking")]
+use crate::networking::{AssistantActions, Networking};
+#[cfg(feature = "async")]
+use crate::networking::{AsyncAssistantActions, AsyncNetworking};
+use crate::types::common::{Identifiable, ToolResources, Tools};
 use crate::types::error::OpenApiError;
 
 /// `Assistant` is a struct that represents an OpenAI assistant object. It represents an entity
 /// that can be configured to respond to users’ Messages using several parameters like:
 /// - Instructions: how the Assistant and model should behave or respond
-/// - Model: you can specify any GPT-3.5 or GPT-4 models. The Retrieval tool requires at least gpt-3.5-turbo-1106 (newer versions are supported) or gpt-4-turbo-preview models.
-/// - Tools: the API supports Code Interpreter and Retrieval that are built and hosted by OpenAI.
+/// - Model: you can specify any GPT-3.5 or GPT-4 models. The File Search tool requires at least gpt-3.5-turbo-1106 (newer versions are supported) or gpt-4-turbo-preview models.
+/// - Tools: the API supports Code Interpreter and File Search that are built and hosted by OpenAI.
 /// - Functions: the API allows you to define custom function signatures, with similar behavior the function calling feature.
 ///
 /// # Fields
@@ -25,8 +26,11 @@ use crate::types::error::OpenApiError;
 /// * `model: String` - The model identifier that the assistant is based on.
 /// * `instructions: Option<String>` - Instructions for the assistant, if provided.
 /// * `tools: Vec<Tools>` - A list of tools associated with the assistant.
-/// * `file_ids: Vec<String>` - A list of file identifiers associated with the assistant.
+/// * `tool_resources: Option<ToolResources>` - The files available to the tools of the assistant.
+/// * `file_ids: Vec<String>` - A list of file identifiers associated with the assistant, only returned by Assistants v1.
 /// * `metadata: HashMap<String, String>` - A map of metadata associated with the assistant.
+/// * `temperature: Option<f64>` - The sampling temperature, between 0 and 2.
+/// * `top_p: Option<f64>` - The nucleus sampling probability mass, between 0 and 1.
 ///
 /// # Implements
 ///
@@ -42,8 +46,25 @@ pub struct Assistant {
     model: String,
     instructions: Option<String>,
     tools: Vec<Tools>,
+    #[serde(default)]
+    tool_resources: Option<ToolResources>,
+    #[serde(default)]
     file_ids: Vec<String>,
     metadata: HashMap<String, String>,
+    #[serde(default)]
+    temperature: Option<f64>,
+    #[serde(default)]
+    top_p: Option<f64>,
+}
+
+impl Assistant {
+    pub fn get_tools(&self) -> &Vec<Tools> {
+        &self.tools
+    }
+
+    pub fn get_tool_resources(&self) -> Option<&ToolResources> {
+        self.tool_resources.as_ref()
+    }
 }
 
 impl Identifiable for Assistant {
@@ -54,6 +75,7 @@ impl Identifiable for Assistant {
 impl_ref!(Assistant, Identifiable);
 
 /// `AssistantFile` is a struct that represents a file associated with an OpenAI assistant object.
+/// Assistant files only exist in Assistants v1, v2 attaches files through `ToolResources`.
 ///
 /// # Fields
 ///
@@ -79,8 +101,10 @@ pub struct AssistantFile {
 /// * `description: Option<String>` - The description of the assistant, if provided.
 /// * `instructions: Option<String>` - Instructions for the assistant, if provided.
 /// * `tools: Option<Vec<Tools>>` - A list of tools associated with the assistant.
-/// * `file_ids: Option<Vec<String>>` - A list of file identifiers associated with the assistant.
+/// * `tool_resources: Option<ToolResources>` - The files available to the tools of the assistant.
 /// * `metadata: Option<HashMap<String, String>>` - A map of metadata associated with the assistant.
+/// * `temperature: Option<f64>` - The sampling temperature, between 0 and 2.
+/// * `top_p: Option<f64>` - The nucleus sampling probability mass, between 0 and 1.
 ///
 #[derive(Default, Debug, Serialize)]
 pub struct AssistantBuilder {
@@ -91,9 +115,13 @@ pub struct AssistantBuilder {
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<Tools>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    file_ids: Option<Vec<String>>,
+    tool_resources: Option<ToolResources>,
     #[serde(skip_serializing_if = "Option::is_none")]
     metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
 }
 
 impl AssistantBuilder {
@@ -105,14 +133,24 @@ impl AssistantBuilder {
     }
 
     pub fn from(assistant: Assistant) -> Self {
+        // Files of a v1 assistant carry over as code interpreter files
+        let tool_resources = match (assistant.tool_resources, assistant.file_ids.is_empty()) {
+            (Some(tool_resources), _) => Some(tool_resources),
+            (None, false) => {
+                Some(ToolResources::new().with_code_interpreter_files(assistant.file_ids))
+            }
+            (None, true) => None,
+        };
         Self {
             model: assistant.model,
             name: assistant.name,
             description: assistant.description,
             instructions: assistant.instructions,
             tools: Some(assistant.tools),
-            file_ids: Some(assistant.file_ids),
+            tool_resources,
             metadata: Some(assistant.metadata),
+            temperature: assistant.temperature,
+            top_p: assistant.top_p,
         }
     }
 
@@ -168,24 +206,47 @@ impl AssistantBuilder {
         self
     }
 
-    pub fn with_file_ids(mut self, file_ids: Vec<String>) -> Self {
-        // Add a list of file ids using a Vec<String>
-        // TODO Add check for current file id count doesn't exceed 20
-        self.file_ids = Some(file_ids);
+    pub fn with_tool_resources(mut self, tool_resources: ToolResources) -> Self {
+        self.tool_resources = Some(tool_resources);
         self
     }
 
-    pub fn add_file_id<T: Identifiable>(mut self, file_id: T) -> Self {
-        // Add an individual file id using an Identifiable
-        // TODO Add check for current file id count doesn't exceed 20
-        let file_id = file_id.get_identifier();
-        if let Some(mut file_ids) = self.file_ids {
-            file_ids.push(file_id);
-            self.file_ids = Some(file_ids);
-        } else {
-            self.file_ids = Some(vec![file_id]);
+    /// Sets the files of the code interpreter, the v2 equivalent of the v1 `file_ids`.
+    #[deprecated(note = "file_ids was removed in Assistants v2, use `with_tool_resources`")]
+    pub fn with_file_ids(self, file_ids: Vec<String>) -> Self {
+        let tool_resources = self.tool_resources.clone().unwrap_or_default();
+        self.with_tool_resources(tool_resources.with_code_interpreter_files(file_ids))
+    }
+
+    /// Adds a file to the code interpreter, the v2 equivalent of the v1 `file_ids`.
+    #[deprecated(note = "file_ids was removed in Assistants v2, use `with_tool_resources`")]
+    pub fn add_file_id<T: Identifiable>(self, file_id: T) -> Self {
+        let tool_resources = self.tool_resources.clone().unwrap_or_default();
+        self.with_tool_resources(tool_resources.add_code_interpreter_file(file_id))
+    }
+
+    pub fn with_temperature(mut self, temperature: f64) -> Result<Self, OpenApiError> {
+        match temperature {
+            0.0..=2.0 => {
+                self.temperature = Some(temperature);
+                Ok(self)
+            }
+            _ => Err(OpenApiError::RestrictedValue(
+                "Temperature must be between 0.0 and 2.0".into(),
+            )),
+        }
+    }
+
+    pub fn with_top_p(mut self, top_p: f64) -> Result<Self, OpenApiError> {
+        match top_p {
+            0.0..=1.0 => {
+                self.top_p = Some(top_p);
+                Ok(self)
+            }
+            _ => Err(OpenApiError::RestrictedValue(
+                "Top P must be between 0.0 and 1.0".into(),
+            )),
         }
-        self
     }
 
     pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
@@ -194,9 +255,18 @@ impl AssistantBuilder {
         self
     }
 
+    #[cfg(feature = "blocking")]
     pub fn build(&self, networking: &Networking) -> Result<Assistant, OpenApiError> {
         networking.create_assistant(self)
     }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(
+        &self,
+        networking: &AsyncNetworking,
+    ) -> Result<Assistant, OpenApiError> {
+        networking.create_assistant(self).await
+    }
 }
 
 /// `AssistantFileBuilder` is a struct that provides a builder pattern for creating an `AssistantFile`.
@@ -221,7 +291,62 @@ impl AssistantFileBuilder {
         }
     }
 
+    #[cfg(feature = "blocking")]
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
     pub fn build(&self, networking: &Networking) -> Result<AssistantFile, OpenApiError> {
         networking.create_assistant_file(self, &self.assistant_id)
     }
+
+    #[cfg(feature = "async")]
+    #[deprecated(
+        note = "assistant files were removed in Assistants v2, use `AssistantBuilder::with_tool_resources`"
+    )]
+    #[allow(deprecated)]
+    pub async fn build_async(
+        &self,
+        networking: &AsyncNetworking,
+    ) -> Result<AssistantFile, OpenApiError> {
+        networking
+            .create_assistant_file(self, &self.assistant_id)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_file_ids_migrated_to_tool_resources() {
+        let builder = AssistantBuilder::new("gpt-4o")
+            .add_tool(Tools::file_search())
+            .add_file_id("file-abc123");
+        let body = serde_json::to_value(&builder).unwrap();
+        assert_eq!(body["tools"][0]["type"], "file_search");
+        assert_eq!(
+            body["tool_resources"]["code_interpreter"]["file_ids"][0],
+            "file-abc123"
+        );
+        assert!(body.get("file_ids").is_none());
+
+        // A v1 assistant still deserializes, and its files carry over
+        let v1 = serde_json::json!({
+            "id": "asst_abc123", "object": "assistant", "created_at": 1699009709,
+            "name": null, "description": null, "model": "gpt-4", "instructions": null,
+            "tools": [{"type": "retrieval"}], "file_ids": ["file-abc123"], "metadata": {}
+        });
+        let assistant: Assistant = serde_json::from_value(v1).unwrap();
+        let builder = AssistantBuilder::from(assistant);
+        assert_eq!(
+            builder
+                .tool_resources
+                .unwrap()
+                .get_code_interpreter_file_ids(),
+            ["file-abc123"]
+        );
+    }
 }
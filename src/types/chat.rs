@@ -1,13 +1,16 @@
-use serde::de::{SeqAccess, Visitor};
-use serde::ser::SerializeSeq;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashMap;
-use std::str::FromStr;
-
-use crate::networking::Networking;
-use crate::types::common::{Identifiable, ToolCalls, Tools, Usage};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(feature = "async")]
+use crate::networking::{AsyncChatCompletionActions, AsyncChatCompletionStream, AsyncNetworking};
+#[cfg(feature = "blocking")]
+use crate::networking::{ChatCompletionActions, ChatCompletionStream, Networking};
+use crate::types::common::{validate_name, Identifiable, ToolCalls, ToolChoice, Tools, Usage};
 use crate::types::error::OpenApiError;
-use crate::types::message::{CompletionMessage, GeneralMessage};
+use crate::types::message::{CompletionMessage, GeneralMessage, MessageRole};
+use crate::types::schema::{JsonSchema, ToolSchema};
 
 #[derive(Default, Serialize, Clone, Deserialize, Debug)]
 pub struct ChatCompletionBuilder {
@@ -21,15 +24,19 @@ pub struct ChatCompletionBuilder {
     max_tokens: Option<i32>,
     n: Option<i8>,
     presence_penalty: Option<f64>,
-    response_format: Option<HashMap<String, String>>, // TODO
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
     seed: Option<i32>,
     stop: Vec<String>,
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
     temperature: Option<f64>,
     top_p: Option<f64>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<Tools>,
-    //tool_choice // TODO: Implement tool_choice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
     user: Option<String>,
 }
 
@@ -42,6 +49,15 @@ impl ChatCompletionBuilder {
         }
     }
 
+    pub fn add_message(mut self, message: CompletionMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    pub fn get_messages(&self) -> &[CompletionMessage] {
+        &self.messages
+    }
+
     pub fn with_frequency_penalty(mut self, frequency_penalty: f64) -> Result<Self, OpenApiError> {
         match frequency_penalty {
             -2.0..=2.0 => {
@@ -60,7 +76,7 @@ impl ChatCompletionBuilder {
     ) -> Result<Self, OpenApiError> {
         // Iterate through and check logit bias vals are valid.
         for (_k, v) in logit_bias.iter() {
-            if v < &-100 || v > &100 {
+            if !(-100..=100).contains(v) {
                 return Err(OpenApiError::RestrictedValue(
                     "Logit Bias values must be between -100 and 100".into(),
                 ));
@@ -83,7 +99,7 @@ impl ChatCompletionBuilder {
     }
 
     pub fn with_top_logprobs(mut self, top_logprobs: i8) -> Result<Self, OpenApiError> {
-        if (self.logprobs.is_none() || self.logprobs.unwrap() == false) {
+        if self.logprobs != Some(true) {
             return Err(OpenApiError::RestrictedValue(
                 "Top Logprobs requires logprobs to be true".into(),
             ));
@@ -124,10 +140,13 @@ impl ChatCompletionBuilder {
         }
     }
 
-    pub fn with_response_format(mut self, response_format: HashMap<String, String>) -> Self {
-        // TODO
+    pub fn with_response_format(
+        mut self,
+        response_format: ResponseFormat,
+    ) -> Result<Self, OpenApiError> {
+        response_format.validate()?;
         self.response_format = Some(response_format);
-        self
+        Ok(self)
     }
 
     pub fn with_seed(mut self, seed: i32) -> Self {
@@ -150,6 +169,13 @@ impl ChatCompletionBuilder {
         self
     }
 
+    /// Requests a final chunk carrying the `usage` of the whole completion when streaming. It is
+    /// left out of requests that are not streamed.
+    pub fn with_include_usage(mut self, include_usage: bool) -> Self {
+        self.stream_options = Some(StreamOptions { include_usage });
+        self
+    }
+
     pub fn with_temperature(mut self, temperature: f64) -> Result<Self, OpenApiError> {
         match temperature {
             0.0..=2.0 => {
@@ -179,8 +205,15 @@ impl ChatCompletionBuilder {
         self
     }
 
-    pub fn with_tool_choice(mut self, tool_choice: String) -> Self {
-        todo!()
+    /// Controls which tool is called. The choice is checked against the tools of the request when
+    /// it is sent.
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn get_tool_choice(&self) -> Option<&ToolChoice> {
+        self.tool_choice.as_ref()
     }
 
     pub fn with_user(mut self, user: String) -> Self {
@@ -188,9 +221,189 @@ impl ChatCompletionBuilder {
         self
     }
 
+    #[cfg(feature = "blocking")]
     pub fn build(&self, networking: &Networking) -> Result<ChatCompletion, OpenApiError> {
         networking.create_chat_completion(self)
     }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(
+        &self,
+        networking: &AsyncNetworking,
+    ) -> Result<ChatCompletion, OpenApiError> {
+        networking.create_chat_completion(self).await
+    }
+
+    /// Streams the completion, see `ChatCompletionStream`. `stream` is set regardless of
+    /// `with_stream`.
+    #[cfg(feature = "blocking")]
+    pub fn build_stream(
+        &self,
+        networking: &Networking,
+    ) -> Result<ChatCompletionStream, OpenApiError> {
+        networking.create_chat_completion_stream(self)
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn build_stream_async(
+        &self,
+        networking: &AsyncNetworking,
+    ) -> Result<AsyncChatCompletionStream, OpenApiError> {
+        networking.create_chat_completion_stream(self).await
+    }
+
+    /// Requests a reply matching the schema of `T`, see `ResponseFormat::json_schema_of`, and
+    /// decodes it with `ChatCompletion::parse_content`.
+    #[cfg(feature = "blocking")]
+    pub fn complete_as<T: ToolSchema + DeserializeOwned>(
+        &self,
+        networking: &Networking,
+    ) -> Result<T, OpenApiError> {
+        let builder = self
+            .clone()
+            .with_response_format(ResponseFormat::json_schema_of::<T>()?)?;
+        builder.build(networking)?.parse_content()
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn complete_as_async<T: ToolSchema + DeserializeOwned>(
+        &self,
+        networking: &AsyncNetworking,
+    ) -> Result<T, OpenApiError> {
+        let builder = self
+            .clone()
+            .with_response_format(ResponseFormat::json_schema_of::<T>()?)?;
+        builder.build_async(networking).await?.parse_content()
+    }
+
+    pub(crate) fn is_stream(&self) -> bool {
+        self.stream.unwrap_or(false)
+    }
+
+    /// Checks the request and serializes its body, dropping `stream_options` when not streaming,
+    /// as the api rejects it there.
+    pub(crate) fn request_body(&self) -> Result<Value, OpenApiError> {
+        if let Some(tool_choice) = &self.tool_choice {
+            tool_choice.validate(&self.tools)?;
+        }
+        let mut body = serde_json::to_value(self)?;
+        if let (false, Some(body)) = (self.is_stream(), body.as_object_mut()) {
+            body.remove("stream_options");
+        }
+        Ok(body)
+    }
+}
+
+/// The format of the reply.
+///
+/// * `Text` - Free text, the default.
+/// * `JsonObject` - JSON mode, where the reply is a JSON object. The messages must ask for JSON.
+/// * `JsonSchema` - Structured outputs, where the reply is JSON matching a schema.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: Box<JsonSchemaFormat> },
+}
+
+impl ResponseFormat {
+    pub fn json_schema(json_schema: JsonSchemaFormat) -> Self {
+        ResponseFormat::JsonSchema {
+            json_schema: Box::new(json_schema),
+        }
+    }
+
+    /// The strict schema of `T`, named after the type and its generic arguments, e.g.
+    /// `my_crate::Page<my_crate::Recipe>` is named `Page_Recipe`. Fails when the name is longer
+    /// than 64 characters, use `json_schema` with an explicit name for such types.
+    pub fn json_schema_of<T: ToolSchema>() -> Result<Self, OpenApiError> {
+        let name = std::any::type_name::<T>()
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+            .filter(|path| !path.is_empty())
+            .map(|path| path.rsplit("::").next().unwrap_or(path))
+            .collect::<Vec<_>>()
+            .join("_");
+        let format = JsonSchemaFormat::new(name, T::tool_schema()).with_strict(true);
+        format.validate()?;
+        Ok(ResponseFormat::json_schema(format))
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), OpenApiError> {
+        match self {
+            ResponseFormat::JsonSchema { json_schema } => json_schema.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The schema of `ResponseFormat::JsonSchema`, which in `strict` mode must follow the same
+/// restrictions as strict function parameters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    schema: JsonSchema,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strict: Option<bool>,
+}
+
+impl JsonSchemaFormat {
+    pub fn new<N: Into<String>>(name: N, schema: JsonSchema) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            schema,
+            strict: None,
+        }
+    }
+
+    pub fn with_description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = Some(strict);
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn get_schema(&self) -> &JsonSchema {
+        &self.schema
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict.unwrap_or(false)
+    }
+
+    fn validate(&self) -> Result<(), OpenApiError> {
+        validate_name("Schema", &self.name)?;
+        if !self.schema.is_object() {
+            return Err(OpenApiError::RestrictedValue(format!(
+                "Schema {} must be an object at its root",
+                self.name
+            )));
+        }
+        match self.is_strict() {
+            true => self.schema.validate_strict(),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Options of a streamed chat completion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -201,17 +414,49 @@ pub struct ChatCompletion {
     model: String,
     system_fingerprint: Option<String>,
     object: String,
-    usage: Usage,
+    /// Only missing when rebuilt from a stream without `with_include_usage`.
+    #[serde(default)]
+    usage: Option<Usage>,
 }
 
 impl ChatCompletion {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
     pub fn get_choices(&self) -> Vec<Choice> {
         self.choices.clone()
     }
 
+    pub fn get_model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn get_usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
     pub fn get_message_content(&self) -> Option<String> {
         self.choices.first()?.get_message()?.get_content().clone()
     }
+
+    /// Decodes the content of the first choice as JSON, e.g. a reply to a `ResponseFormat` with
+    /// a schema. Fails with `OpenApiError::Refusal` when the model refused, and with
+    /// `OpenApiError::SchemaViolation` when the content does not decode into `T`.
+    pub fn parse_content<T: DeserializeOwned>(&self) -> Result<T, OpenApiError> {
+        let message = self
+            .choices
+            .first()
+            .and_then(|choice| choice.message.as_ref())
+            .ok_or_else(|| OpenApiError::ClientError("Chat completion has no message".into()))?;
+        if let Some(refusal) = message.get_refusal() {
+            return Err(OpenApiError::Refusal(refusal.to_string()));
+        }
+        let content = message
+            .get_content()
+            .ok_or_else(|| OpenApiError::ClientError("Chat completion has no content".into()))?;
+        serde_json::from_str(&content).map_err(OpenApiError::SchemaViolation)
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -222,6 +467,32 @@ pub struct ChatCompletionChunk {
     model: String,
     system_fingerprint: Option<String>,
     object: String,
+    /// Only set on the final chunk, and only when requested with `with_include_usage`.
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+impl ChatCompletionChunk {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_choices(&self) -> &[Choice] {
+        &self.choices
+    }
+
+    pub fn get_model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn get_usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
+    /// Returns the content added by this chunk to the first choice.
+    pub fn get_delta_content(&self) -> Option<&str> {
+        self.choices.first()?.delta.as_ref()?.content.as_deref()
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -237,13 +508,162 @@ impl Choice {
     pub fn get_message(&self) -> Option<GeneralMessage> {
         self.message.clone()
     }
+
+    pub fn get_index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn get_finish_reason(&self) -> Option<&str> {
+        self.finish_reason.as_deref()
+    }
+
+    /// Returns the part of the message added by a `ChatCompletionChunk`.
+    pub fn get_delta(&self) -> Option<&Delta> {
+        self.delta.as_ref()
+    }
 }
 
+/// The part of a message added by a `ChatCompletionChunk`, where every field is only present
+/// when it changes. Tool calls arrive in fragments, see `ChatCompletionAccumulator`.
 #[derive(Clone, Deserialize, Serialize, Debug)]
-struct Delta {
+pub struct Delta {
     content: Option<String>,
+    #[serde(default)]
     tool_calls: Vec<ToolCalls>,
-    role: String,
+    role: Option<String>,
+}
+
+impl Delta {
+    pub fn get_content(&self) -> Option<&str> {
+        self.content.as_deref()
+    }
+
+    pub fn get_tool_calls(&self) -> &[ToolCalls] {
+        &self.tool_calls
+    }
+
+    pub fn get_role(&self) -> Option<&str> {
+        self.role.as_deref()
+    }
+}
+
+/// `ChatCompletionAccumulator` rebuilds the `ChatCompletion` of a streamed request from its
+/// `ChatCompletionChunk`s, e.g. for logging once the stream is complete.
+///
+/// The content of every choice is concatenated, tool call fragments are merged by their
+/// `index`, and the last `finish_reason` and `usage` received are kept.
+///
+/// # Examples
+/// ```ignore
+/// let mut accumulator = ChatCompletionAccumulator::new();
+/// for chunk in builder.build_stream(client.netref())? {
+///     let chunk = chunk?;
+///     print!("{}", chunk.get_delta_content().unwrap_or_default());
+///     accumulator.add_chunk(&chunk);
+/// }
+/// let completion = accumulator.finish()?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChatCompletionAccumulator {
+    id: Option<String>,
+    created: i64,
+    model: String,
+    system_fingerprint: Option<String>,
+    choices: BTreeMap<u32, ChoiceAccumulator>,
+    usage: Option<Usage>,
+}
+
+/// The state of a single choice, tool calls are keyed by their index.
+#[derive(Clone, Debug, Default)]
+struct ChoiceAccumulator {
+    role: Option<String>,
+    content: Option<String>,
+    tool_calls: BTreeMap<u32, ToolCalls>,
+    finish_reason: Option<String>,
+}
+
+impl ChatCompletionAccumulator {
+    pub fn new() -> Self {
+        ChatCompletionAccumulator::default()
+    }
+
+    /// Merges the deltas of `chunk` into the completion.
+    pub fn add_chunk(&mut self, chunk: &ChatCompletionChunk) {
+        if self.id.is_none() {
+            self.id = Some(chunk.id.clone());
+            self.created = chunk.created;
+            self.model = chunk.model.clone();
+        }
+        if chunk.system_fingerprint.is_some() {
+            self.system_fingerprint = chunk.system_fingerprint.clone();
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage.clone();
+        }
+        for choice in &chunk.choices {
+            let state = self.choices.entry(choice.index).or_default();
+            if choice.finish_reason.is_some() {
+                state.finish_reason = choice.finish_reason.clone();
+            }
+            let Some(delta) = &choice.delta else {
+                continue;
+            };
+            if delta.role.is_some() {
+                state.role = delta.role.clone();
+            }
+            if let Some(content) = &delta.content {
+                state
+                    .content
+                    .get_or_insert_with(String::new)
+                    .push_str(content);
+            }
+            for (position, fragment) in delta.tool_calls.iter().enumerate() {
+                // The index is always sent when streaming, the position is only a fallback
+                let index = fragment.get_index().unwrap_or(position as u32);
+                match state.tool_calls.get_mut(&index) {
+                    Some(tool_call) => tool_call.merge(fragment),
+                    None => {
+                        state.tool_calls.insert(index, fragment.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the completion built from the chunks added so far, which fails when none were.
+    pub fn finish(self) -> Result<ChatCompletion, OpenApiError> {
+        let id = self.id.ok_or_else(|| {
+            OpenApiError::ClientError("No chunks were added to the accumulator".into())
+        })?;
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|(index, choice)| {
+                let tool_calls: Vec<ToolCalls> = choice.tool_calls.into_values().collect();
+                let role = MessageRole::from(choice.role.as_deref().unwrap_or("assistant"));
+                Choice {
+                    finish_reason: choice.finish_reason,
+                    index,
+                    logprobs: None,
+                    message: Some(GeneralMessage::new_reply(
+                        role,
+                        choice.content,
+                        (!tool_calls.is_empty()).then_some(tool_calls),
+                    )),
+                    delta: None,
+                }
+            })
+            .collect();
+        Ok(ChatCompletion {
+            id,
+            choices,
+            created: self.created,
+            model: self.model,
+            system_fingerprint: self.system_fingerprint,
+            object: String::from("chat.completion"),
+            usage: self.usage,
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -263,4 +683,277 @@ struct LogProb {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    fn chunk(choices: serde_json::Value, usage: serde_json::Value) -> ChatCompletionChunk {
+        serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1700000000,
+            "model": "gpt-4",
+            "system_fingerprint": "fp_1",
+            "choices": choices,
+            "usage": usage,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_accumulator_rebuilds_completion() {
+        let chunks = [
+            chunk(
+                json!([
+                    {"index": 0, "delta": {"role": "assistant", "content": "Hel"}, "finish_reason": null},
+                    {"index": 1, "delta": {"role": "assistant", "tool_calls": [
+                        {"index": 0, "id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": ""}}
+                    ]}, "finish_reason": null}
+                ]),
+                json!(null),
+            ),
+            chunk(
+                json!([
+                    {"index": 0, "delta": {"content": "lo"}, "finish_reason": null},
+                    {"index": 1, "delta": {"tool_calls": [
+                        {"index": 0, "function": {"arguments": "{\"city\":"}},
+                        {"index": 1, "id": "call_2", "type": "function", "function": {"name": "get_time", "arguments": "{}"}}
+                    ]}, "finish_reason": null}
+                ]),
+                json!(null),
+            ),
+            chunk(
+                json!([
+                    {"index": 0, "delta": {}, "finish_reason": "stop"},
+                    {"index": 1, "delta": {"tool_calls": [
+                        {"index": 0, "function": {"arguments": "\"Paris\"}"}}
+                    ]}, "finish_reason": "tool_calls"}
+                ]),
+                json!(null),
+            ),
+            chunk(
+                json!([]),
+                json!({"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}),
+            ),
+        ];
+        let mut accumulator = ChatCompletionAccumulator::new();
+        chunks.iter().for_each(|c| accumulator.add_chunk(c));
+        let completion = accumulator.finish().unwrap();
+
+        assert_eq!(completion.get_id(), "chatcmpl-1");
+        assert_eq!(completion.get_message_content().unwrap(), "Hello");
+        assert_eq!(completion.get_usage().unwrap().get_total_tokens(), 15);
+        let choices = completion.get_choices();
+        assert_eq!(choices[0].get_finish_reason(), Some("stop"));
+        assert_eq!(choices[1].get_finish_reason(), Some("tool_calls"));
+
+        let message = choices[1].get_message().unwrap();
+        let tool_calls = message.get_tool_calls();
+        assert_eq!(tool_calls.len(), 2);
+        assert_eq!(tool_calls[0].get_id(), "call_1");
+        assert_eq!(tool_calls[0].get_function().get_name(), "get_weather");
+        assert_eq!(
+            tool_calls[0].get_function().get_arguments(),
+            r#"{"city":"Paris"}"#
+        );
+        assert_eq!(tool_calls[1].get_function().get_name(), "get_time");
+    }
+
+    #[test]
+    fn test_tool_choice_validated_and_serialized() {
+        let tools: Vec<Tools> = serde_json::from_value(json!([
+            {"type": "function", "function": {"name": "get_weather", "description": "Weather"}}
+        ]))
+        .unwrap();
+        let builder = ChatCompletionBuilder::new("gpt-4", Vec::new()).with_tools(tools.clone());
+        assert!(builder
+            .clone()
+            .with_tool_choice(ToolChoice::function("get_time"))
+            .request_body()
+            .is_err());
+        for tool_choice in [ToolChoice::None, ToolChoice::Auto, ToolChoice::Required] {
+            assert!(ChatCompletionBuilder::new("gpt-4", Vec::new())
+                .with_tool_choice(tool_choice)
+                .request_body()
+                .is_err());
+        }
+        // Checked once the request is sent, so the order of the builder calls does not matter
+        assert!(ChatCompletionBuilder::new("gpt-4", Vec::new())
+            .with_tool_choice(ToolChoice::function("get_weather"))
+            .with_tools(tools)
+            .request_body()
+            .is_ok());
+        assert!(builder
+            .clone()
+            .with_tool_choice(ToolChoice::function("get_weather"))
+            .with_tools(Vec::new())
+            .request_body()
+            .is_err());
+
+        let body = builder
+            .with_tool_choice(ToolChoice::function("get_weather"))
+            .request_body()
+            .unwrap();
+        assert_eq!(
+            body["tool_choice"],
+            json!({"type": "function", "function": {"name": "get_weather"}})
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoice::Required).unwrap(),
+            "required"
+        );
+        let choice: ToolChoice = serde_json::from_value(json!("none")).unwrap();
+        assert_eq!(choice, ToolChoice::None);
+    }
+
+    #[test]
+    fn test_accumulator_requires_chunks() {
+        assert!(ChatCompletionAccumulator::new().finish().is_err());
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Recipe {
+        title: String,
+        minutes: u32,
+    }
+
+    impl ToolSchema for Recipe {
+        fn tool_schema() -> JsonSchema {
+            JsonSchema::object()
+                .with_property("title", JsonSchema::string())
+                .with_property("minutes", JsonSchema::integer())
+        }
+    }
+
+    fn completion(message: serde_json::Value) -> ChatCompletion {
+        serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "gpt-4o",
+            "system_fingerprint": null,
+            "choices": [{"index": 0, "message": message, "logprobs": null, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 9, "completion_tokens": 1, "total_tokens": 10},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_response_format_serialized() {
+        assert_eq!(
+            serde_json::to_value(ResponseFormat::JsonObject).unwrap(),
+            json!({"type": "json_object"})
+        );
+        assert_eq!(
+            serde_json::to_value(ResponseFormat::json_schema_of::<Recipe>().unwrap()).unwrap(),
+            json!({"type": "json_schema", "json_schema": {
+                "name": "Recipe",
+                "schema": Recipe::tool_schema(),
+                "strict": true,
+            }})
+        );
+
+        let optional = JsonSchema::object().with_optional_property("title", JsonSchema::string());
+        let builder = ChatCompletionBuilder::new("gpt-4o", Vec::new());
+        let format = JsonSchemaFormat::new("Recipe", optional);
+        assert!(builder
+            .clone()
+            .with_response_format(ResponseFormat::json_schema(
+                format.clone().with_strict(true)
+            ))
+            .is_err());
+        assert!(builder
+            .with_response_format(ResponseFormat::json_schema(format))
+            .is_ok());
+    }
+
+    struct Page<T>(std::marker::PhantomData<T>);
+
+    impl<T: ToolSchema> ToolSchema for Page<T> {
+        fn tool_schema() -> JsonSchema {
+            JsonSchema::object().with_property("items", Vec::<T>::tool_schema())
+        }
+    }
+
+    struct RecipeWithAnExceptionallyLongNameThatRunsPastTheLimitOfSixtyFourChars;
+
+    impl ToolSchema for RecipeWithAnExceptionallyLongNameThatRunsPastTheLimitOfSixtyFourChars {
+        fn tool_schema() -> JsonSchema {
+            JsonSchema::object()
+        }
+    }
+
+    #[test]
+    fn test_json_schema_of_named_and_validated() {
+        let name = |format: ResponseFormat| match format {
+            ResponseFormat::JsonSchema { json_schema } => json_schema.get_name().to_string(),
+            other => panic!("unexpected format {:?}", other),
+        };
+        assert_eq!(
+            name(ResponseFormat::json_schema_of::<Page<Recipe>>().unwrap()),
+            "Page_Recipe"
+        );
+        assert_eq!(
+            name(ResponseFormat::json_schema_of::<Page<String>>().unwrap()),
+            "Page_String"
+        );
+
+        assert!(ResponseFormat::json_schema_of::<
+            RecipeWithAnExceptionallyLongNameThatRunsPastTheLimitOfSixtyFourChars,
+        >()
+        .is_err());
+        assert!(ResponseFormat::json_schema_of::<Vec<Recipe>>().is_err());
+        assert!(ResponseFormat::json_schema_of::<String>().is_err());
+    }
+
+    #[test]
+    fn test_parse_content() {
+        let recipe: Recipe = completion(json!({
+            "role": "assistant",
+            "content": r#"{"title": "Pancakes", "minutes": 20}"#,
+        }))
+        .parse_content()
+        .unwrap();
+        assert_eq!(recipe.title, "Pancakes");
+        assert_eq!(recipe.minutes, 20);
+
+        let refused = completion(json!({
+            "role": "assistant",
+            "content": null,
+            "refusal": "I can't help with that.",
+        }));
+        assert!(matches!(
+            refused.parse_content::<Recipe>(),
+            Err(OpenApiError::Refusal(refusal)) if refusal == "I can't help with that."
+        ));
+
+        let invalid = completion(json!({"role": "assistant", "content": r#"{"title": 1}"#}));
+        assert!(matches!(
+            invalid.parse_content::<Recipe>(),
+            Err(OpenApiError::SchemaViolation(_))
+        ));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_complete_as() {
+        use crate::networking::transport::MockTransport;
+        use crate::networking::{Networking, NetworkingConfig};
+        use reqwest::{Method, StatusCode};
+
+        let reply = completion(json!({
+            "role": "assistant",
+            "content": r#"{"title": "Pancakes", "minutes": 20}"#,
+        }));
+        let mock = MockTransport::new()
+            .with_json(Method::POST, "chat/completions", StatusCode::OK, &reply)
+            .unwrap();
+        let networking = Networking::new(NetworkingConfig::default()).with_transport(mock.clone());
+        let recipe: Recipe = ChatCompletionBuilder::new("gpt-4o", Vec::new())
+            .complete_as(&networking)
+            .unwrap();
+        assert_eq!(recipe.title, "Pancakes");
+
+        let request = mock.requests()[0].json_body().unwrap().clone();
+        assert_eq!(request["response_format"]["type"], "json_schema");
+        assert_eq!(request["response_format"]["json_schema"]["strict"], true);
+    }
 }
@@ -1,5 +1,9 @@
 use crate::impl_ref;
+use crate::types::error::OpenApiError;
+use crate::types::schema::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Index;
 
 /// `Identifiable` is a trait that provides a method for retrieving the identifier field of an object.
 ///
@@ -55,19 +59,196 @@ pub struct ApiList<T> {
 impl<T> ApiList<T> {
     /// Returns a reference to the data field of the ApiList.
     pub fn get_data_vec(&self) -> &Vec<T> {
-        return &self.data;
+        &self.data
+    }
+
+    pub fn get_first_id(&self) -> Option<&str> {
+        self.first_id.as_deref()
+    }
+
+    pub fn get_last_id(&self) -> Option<&str> {
+        self.last_id.as_deref()
+    }
+
+    /// Returns whether further objects follow this page, see `ListParams::with_after`.
+    pub fn has_more(&self) -> bool {
+        self.has_more.unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+}
+
+impl<T> IntoIterator for ApiList<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ApiList<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<T> Index<usize> for ApiList<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+/// `ListParams` holds the pagination parameters accepted by the list endpoints, sent as the query
+/// string of the request.
+///
+/// # Fields
+///
+/// * `limit: Option<u32>` - The number of objects returned, between 1 and 100, by default 20.
+/// * `order: Option<ListOrder>` - The sort order by `created_at`, by default descending.
+/// * `after: Option<String>` - A cursor, returning the objects after the given id.
+/// * `before: Option<String>` - A cursor, returning the objects before the given id.
+///
+/// # Examples
+/// ```ignore
+/// let params = ListParams::new()
+///     .with_limit(100)?
+///     .with_order(ListOrder::Asc)
+///     .with_after(page.get_last_id().unwrap());
+/// let page = client.list_messages(&thread, Some(&params))?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<ListOrder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before: Option<String>,
+}
+
+/// The sort order of a list by `created_at`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListOrder {
+    Asc,
+    Desc,
+}
+
+impl ListParams {
+    pub fn new() -> Self {
+        ListParams::default()
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Result<Self, OpenApiError> {
+        match limit {
+            1..=100 => {
+                self.limit = Some(limit);
+                Ok(self)
+            }
+            _ => Err(OpenApiError::RestrictedValue(
+                "Limit must be between 1 and 100".into(),
+            )),
+        }
+    }
+
+    pub fn with_order(mut self, order: ListOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    pub fn with_after<I: Identifiable>(mut self, after: I) -> Self {
+        self.after = Some(after.get_identifier());
+        self
+    }
+
+    pub fn with_before<I: Identifiable>(mut self, before: I) -> Self {
+        self.before = Some(before.get_identifier());
+        self
+    }
+
+    pub fn get_limit(&self) -> Option<u32> {
+        self.limit
+    }
+
+    pub fn get_order(&self) -> Option<ListOrder> {
+        self.order
+    }
+
+    pub fn get_after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+
+    pub fn get_before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// Appends the parameters to `endpoint` as a query string, leaving it untouched when `params`
+    /// is `None` or empty.
+    pub(crate) fn apply(
+        endpoint: String,
+        params: Option<&ListParams>,
+    ) -> Result<String, OpenApiError> {
+        let query = match params {
+            Some(params) => serde_urlencoded::to_string(params)
+                .map_err(|e| OpenApiError::ClientError(format!("Invalid list params: {}", e)))?,
+            None => String::new(),
+        };
+        match query.is_empty() {
+            true => Ok(endpoint),
+            false => Ok(format!("{}?{}", endpoint, query)),
+        }
     }
 }
 
 // TODO: ADD DOCUMENTATION FOR BELOW
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct Usage {
     completion_tokens: u32,
     prompt_tokens: u32,
     total_tokens: u32,
 }
 
+impl Usage {
+    pub fn get_completion_tokens(&self) -> u32 {
+        self.completion_tokens
+    }
+
+    pub fn get_prompt_tokens(&self) -> u32 {
+        self.prompt_tokens
+    }
+
+    pub fn get_total_tokens(&self) -> u32 {
+        self.total_tokens
+    }
+}
+
+impl std::ops::AddAssign<&Usage> for Usage {
+    fn add_assign(&mut self, other: &Usage) {
+        self.completion_tokens += other.completion_tokens;
+        self.prompt_tokens += other.prompt_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Tools {
     r#type: ToolTypes,
@@ -76,6 +257,15 @@ pub struct Tools {
 }
 
 impl Tools {
+    /// A function tool, checking the name and, in `strict` mode, the parameters of `function`.
+    pub fn function(function: ToolFunction) -> Result<Self, OpenApiError> {
+        function.validate()?;
+        Ok(Self {
+            r#type: ToolTypes::Function,
+            function: Some(function),
+        })
+    }
+
     pub fn code_interpreter() -> Self {
         Self {
             r#type: ToolTypes::CodeInterpreter,
@@ -83,45 +273,416 @@ impl Tools {
         }
     }
 
+    /// The v2 successor of `retrieval`, searching the vector stores in `ToolResources`.
+    pub fn file_search() -> Self {
+        Self {
+            r#type: ToolTypes::FileSearch,
+            function: None,
+        }
+    }
+
+    #[deprecated(note = "retrieval is only supported by Assistants v1, use `Tools::file_search`")]
     pub fn retrieval() -> Self {
         Self {
             r#type: ToolTypes::Retrieval,
             function: None,
         }
     }
+
+    pub fn get_type(&self) -> &ToolTypes {
+        &self.r#type
+    }
+
+    pub fn get_function(&self) -> Option<&ToolFunction> {
+        self.function.as_ref()
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolTypes {
     CodeInterpreter,
+    FileSearch,
+    /// Only supported by Assistants v1, replaced by `FileSearch`.
     Retrieval,
     Function,
 }
 
+/// `ToolChoice` controls which tool, if any, the model calls. The api rejects any choice, even
+/// `None` or `Auto`, on a request without tools.
+///
+/// # Variants
+///
+/// * `None` - No tool is called and a message is generated instead.
+/// * `Auto` - The model picks between generating a message and calling tools, the default when
+///   tools are present.
+/// * `Required` - At least one tool is called.
+/// * `Function(String)` - The function tool with the given name is called.
+///
+/// # Examples
+/// ```ignore
+/// let builder = ChatCompletionBuilder::new("gpt-4", messages)
+///     .with_tools(tools)
+///     .with_tool_choice(ToolChoice::function("get_weather"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "ToolChoiceRepr", try_from = "ToolChoiceRepr")]
+pub enum ToolChoice {
+    None,
+    Auto,
+    Required,
+    Function(String),
+}
+
+/// The wire format of `ToolChoice`, either a mode or an object naming the function.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ToolChoiceRepr {
+    Mode(String),
+    Named {
+        r#type: ToolTypes,
+        function: NamedFunction,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct NamedFunction {
+    name: String,
+}
+
+impl ToolChoice {
+    pub fn function<N: Into<String>>(name: N) -> Self {
+        ToolChoice::Function(name.into())
+    }
+
+    /// Checks the choice can be satisfied by `tools`, the tools of the request.
+    pub(crate) fn validate(&self, tools: &[Tools]) -> Result<(), OpenApiError> {
+        match self {
+            _ if tools.is_empty() => Err(OpenApiError::RestrictedValue(
+                "Tool choice needs at least one tool".into(),
+            )),
+            ToolChoice::Function(name)
+                if !tools
+                    .iter()
+                    .filter_map(Tools::get_function)
+                    .any(|function| function.get_name() == name) =>
+            {
+                Err(OpenApiError::RestrictedValue(format!(
+                    "Tool choice names function {} which is not among the tools",
+                    name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<ToolChoice> for ToolChoiceRepr {
+    fn from(tool_choice: ToolChoice) -> Self {
+        match tool_choice {
+            ToolChoice::None => ToolChoiceRepr::Mode(String::from("none")),
+            ToolChoice::Auto => ToolChoiceRepr::Mode(String::from("auto")),
+            ToolChoice::Required => ToolChoiceRepr::Mode(String::from("required")),
+            ToolChoice::Function(name) => ToolChoiceRepr::Named {
+                r#type: ToolTypes::Function,
+                function: NamedFunction { name },
+            },
+        }
+    }
+}
+
+impl TryFrom<ToolChoiceRepr> for ToolChoice {
+    type Error = String;
+
+    fn try_from(repr: ToolChoiceRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ToolChoiceRepr::Mode(mode) => match mode.as_str() {
+                "none" => Ok(ToolChoice::None),
+                "auto" => Ok(ToolChoice::Auto),
+                "required" => Ok(ToolChoice::Required),
+                other => Err(format!("unknown tool choice {}", other)),
+            },
+            ToolChoiceRepr::Named {
+                r#type: ToolTypes::Function,
+                function,
+            } => Ok(ToolChoice::Function(function.name)),
+            ToolChoiceRepr::Named { r#type, .. } => {
+                Err(format!("unsupported tool choice type {:?}", r#type))
+            }
+        }
+    }
+}
+
+/// `ToolResources` holds the files made available to the tools of an assistant or thread, which
+/// replaces the `file_ids` of Assistants v1.
+///
+/// # Fields
+///
+/// * `code_interpreter: Option<CodeInterpreterResources>` - The files available to the code interpreter.
+/// * `file_search: Option<FileSearchResources>` - The vector stores searched by file search.
+///
+/// # Examples
+/// ```ignore
+/// let resources = ToolResources::new()
+///     .add_code_interpreter_file(&file)
+///     .add_vector_store_id("vs_abc123");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolResources {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_interpreter: Option<CodeInterpreterResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_search: Option<FileSearchResources>,
+}
+
+/// The files available to the code interpreter, at most 20.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodeInterpreterResources {
+    #[serde(default)]
+    file_ids: Vec<String>,
+}
+
+/// The vector stores searched by file search. `vector_stores` creates a new vector store from
+/// files and is only accepted when creating an assistant or thread.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileSearchResources {
+    #[serde(default)]
+    vector_store_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vector_stores: Option<Vec<VectorStoreFiles>>,
+}
+
+/// The files of a vector store created alongside an assistant or thread.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VectorStoreFiles {
+    file_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<HashMap<String, String>>,
+}
+
+impl ToolResources {
+    pub fn new() -> Self {
+        ToolResources::default()
+    }
+
+    pub fn with_code_interpreter_files(mut self, file_ids: Vec<String>) -> Self {
+        self.code_interpreter = Some(CodeInterpreterResources { file_ids });
+        self
+    }
+
+    pub fn add_code_interpreter_file<I: Identifiable>(mut self, file_id: I) -> Self {
+        self.code_interpreter
+            .get_or_insert_with(CodeInterpreterResources::default)
+            .file_ids
+            .push(file_id.get_identifier());
+        self
+    }
+
+    pub fn with_vector_store_ids(mut self, vector_store_ids: Vec<String>) -> Self {
+        self.file_search
+            .get_or_insert_with(FileSearchResources::default)
+            .vector_store_ids = vector_store_ids;
+        self
+    }
+
+    pub fn add_vector_store_id<I: Identifiable>(mut self, vector_store_id: I) -> Self {
+        self.file_search
+            .get_or_insert_with(FileSearchResources::default)
+            .vector_store_ids
+            .push(vector_store_id.get_identifier());
+        self
+    }
+
+    /// Creates a new vector store from `file_ids` for file search.
+    pub fn add_vector_store(
+        mut self,
+        file_ids: Vec<String>,
+        metadata: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.file_search
+            .get_or_insert_with(FileSearchResources::default)
+            .vector_stores
+            .get_or_insert_with(Vec::new)
+            .push(VectorStoreFiles { file_ids, metadata });
+        self
+    }
+
+    pub fn get_code_interpreter_file_ids(&self) -> &[String] {
+        self.code_interpreter
+            .as_ref()
+            .map(|resources| resources.file_ids.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get_vector_store_ids(&self) -> &[String] {
+        self.file_search
+            .as_ref()
+            .map(|resources| resources.vector_store_ids.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// `ToolFunction` describes a function the model may call, see `Tools::function`.
+///
+/// # Fields
+///
+/// * `name: String` - The name of the function, of at most 64 letters, digits, underscores or dashes.
+/// * `description: String` - What the function does, used by the model to decide when to call it.
+/// * `parameters: Option<JsonSchema>` - The arguments the function accepts, none when omitted.
+/// * `strict: Option<bool>` - Whether the arguments generated must match `parameters` exactly.
+///
+/// # Examples
+/// ```ignore
+/// let tool = Tools::function(
+///     ToolFunction::new("get_weather")
+///         .with_description("Returns the current weather of a city")
+///         .with_parameters(JsonSchema::object().with_property("city", JsonSchema::string()))
+///         .with_strict(true),
+/// )?;
+/// ```
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ToolFunction {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     description: String,
     name: String,
-    // TODO: Implement parameters into ToolFunctions
-    //#[serde(skip_serializing_if = "Option::is_none")]
-    //parameters: Option<>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<JsonSchema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strict: Option<bool>,
+}
+
+impl ToolFunction {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self {
+            description: String::new(),
+            name: name.into(),
+            parameters: None,
+            strict: None,
+        }
+    }
+
+    pub fn with_description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_parameters(mut self, parameters: JsonSchema) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+
+    /// Requires the generated arguments to match `parameters` exactly, which restricts the
+    /// schema, see `JsonSchema`.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = Some(strict);
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn get_parameters(&self) -> Option<&JsonSchema> {
+        self.parameters.as_ref()
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict.unwrap_or(false)
+    }
+
+    fn validate(&self) -> Result<(), OpenApiError> {
+        validate_name("Function", &self.name)?;
+        match (&self.parameters, self.is_strict()) {
+            (Some(parameters), true) => parameters.validate_strict(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks a name against the pattern the api accepts for functions and schemas,
+/// `^[a-zA-Z0-9_-]{1,64}$`.
+pub(crate) fn validate_name(kind: &str, name: &str) -> Result<(), OpenApiError> {
+    let valid_name = (1..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_name {
+        return Err(OpenApiError::RestrictedValue(format!(
+            "{} name {} must be 1 to 64 letters, digits, underscores or dashes",
+            kind, name
+        )));
+    }
+    Ok(())
 }
 
+/// A tool call requested by the model. Within a streamed `ChatCompletionChunk` only the first
+/// fragment of a call carries the `id`, `type` and function `name`, so these default to empty.
 #[derive(Serialize, Clone, Deserialize, Debug)]
 pub struct ToolCalls {
+    #[serde(skip_serializing_if = "Option::is_none")]
     index: Option<u32>,
+    #[serde(default)]
     id: String,
+    #[serde(default)]
     r#type: String,
     function: ToolCallsFunction,
 }
 
+impl ToolCalls {
+    pub fn get_index(&self) -> Option<u32> {
+        self.index
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_type(&self) -> &str {
+        &self.r#type
+    }
+
+    pub fn get_function(&self) -> &ToolCallsFunction {
+        &self.function
+    }
+
+    /// Appends a fragment of the same call received in a later `ChatCompletionChunk`.
+    pub(crate) fn merge(&mut self, fragment: &ToolCalls) {
+        if self.id.is_empty() {
+            self.id = fragment.id.clone();
+        }
+        if self.r#type.is_empty() {
+            self.r#type = fragment.r#type.clone();
+        }
+        self.function.name.push_str(&fragment.function.name);
+        self.function
+            .arguments
+            .push_str(&fragment.function.arguments);
+    }
+}
+
 #[derive(Serialize, Clone, Deserialize, Debug)]
 pub struct ToolCallsFunction {
+    #[serde(default)]
     name: String,
+    #[serde(default)]
     arguments: String,
 }
 
+impl ToolCallsFunction {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the arguments as generated by the model, a JSON object which may be invalid.
+    pub fn get_arguments(&self) -> &str {
+        &self.arguments
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DeletionStatus {
     id: String,
@@ -1,7 +1,15 @@
-use std::fmt::{write, Formatter};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::Formatter;
+use std::time::Duration;
 
+use crate::networking::retry_after;
+
+/// `OperationError` describes a client side operation that could not be completed.
 #[derive(Debug)]
-struct OperationError {
+pub struct OperationError {
     created: u64,
     origin: String,
     reason: String,
@@ -20,6 +28,24 @@ impl OperationError {
             thread_id: format!("{:?}", std::thread::current().id()),
         }
     }
+
+    /// Returns the unix timestamp, in seconds, at which the error was created.
+    pub fn get_created(&self) -> u64 {
+        self.created
+    }
+
+    pub fn get_origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Returns the id of the thread the error was created on.
+    pub fn get_thread_id(&self) -> &str {
+        &self.thread_id
+    }
 }
 
 #[derive(Debug)]
@@ -35,12 +61,155 @@ pub enum OpenApiError {
     RestrictedValue(String),
     ClientError(String),
     OperationError(OperationError), //
+    InvalidToolArguments(String, serde_json::Error),
+    Refusal(String),
+    SchemaViolation(serde_json::Error),
+    // Api Errors
+    Api(Box<ApiError>),
 }
 
 impl OpenApiError {
     pub fn new_operation_err<O: Into<String>, R: Into<String>>(origin: O, reason: R) -> Self {
         OpenApiError::OperationError(OperationError::new(origin.into(), reason.into()))
     }
+
+    /// Builds an `OpenApiError::Api` from a non-success response, decoding the
+    /// `{"error": {...}}` envelope when present and falling back to the raw body otherwise.
+    pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let request_id = headers
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let retry_after = retry_after(headers);
+        let envelope = serde_json::from_slice::<ApiErrorEnvelope>(body).ok();
+        let (message, error_type, param, code) = match envelope {
+            Some(ApiErrorEnvelope { error }) => (
+                error.message.unwrap_or_default(),
+                error.r#type,
+                error.param,
+                ApiErrorCode::from_value(error.code.as_ref()),
+            ),
+            None => (
+                String::from_utf8_lossy(body).into_owned(),
+                None,
+                None,
+                ApiErrorCode::Unknown,
+            ),
+        };
+        OpenApiError::Api(Box::new(ApiError {
+            status,
+            request_id,
+            message,
+            error_type,
+            param,
+            code,
+            retry_after,
+        }))
+    }
+}
+
+/// `ApiError` is the error returned by the OpenAI API alongside a non-success status code.
+///
+/// # Fields
+///
+/// * `status: StatusCode` - The HTTP status code of the response.
+/// * `request_id: Option<String>` - The `x-request-id` header of the response, useful when contacting support.
+/// * `message: String` - A human readable description of the error.
+/// * `error_type: Option<String>` - The error type, e.g. `invalid_request_error`.
+/// * `param: Option<String>` - The request parameter the error relates to, if any.
+/// * `code: ApiErrorCode` - The typed error code.
+/// * `retry_after: Option<Duration>` - The delay requested through the `Retry-After` or `x-ratelimit-reset-*` headers, if any.
+///
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    status: StatusCode,
+    request_id: Option<String>,
+    message: String,
+    error_type: Option<String>,
+    param: Option<String>,
+    code: ApiErrorCode,
+    retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    pub fn get_status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn get_request_id(&self) -> Option<&String> {
+        self.request_id.as_ref()
+    }
+
+    pub fn get_message(&self) -> &String {
+        &self.message
+    }
+
+    pub fn get_error_type(&self) -> Option<&String> {
+        self.error_type.as_ref()
+    }
+
+    pub fn get_param(&self) -> Option<&String> {
+        self.param.as_ref()
+    }
+
+    pub fn get_code(&self) -> &ApiErrorCode {
+        &self.code
+    }
+
+    pub fn get_retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+}
+
+/// Typed representation of the `code` field of an OpenAI error. Codes not known to the library are
+/// kept in `Other`, and responses without a code are `Unknown`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiErrorCode {
+    InvalidApiKey,
+    RateLimitExceeded,
+    InsufficientQuota,
+    ContextLengthExceeded,
+    ModelNotFound,
+    ServerError,
+    Other(String),
+    Unknown,
+}
+
+impl ApiErrorCode {
+    fn from_value(code: Option<&Value>) -> Self {
+        match code {
+            Some(Value::String(code)) => ApiErrorCode::from(code.as_str()),
+            Some(Value::Number(code)) => ApiErrorCode::Other(code.to_string()),
+            _ => ApiErrorCode::Unknown,
+        }
+    }
+}
+
+impl From<&str> for ApiErrorCode {
+    fn from(s: &str) -> Self {
+        match s {
+            "invalid_api_key" => ApiErrorCode::InvalidApiKey,
+            "rate_limit_exceeded" => ApiErrorCode::RateLimitExceeded,
+            "insufficient_quota" => ApiErrorCode::InsufficientQuota,
+            "context_length_exceeded" => ApiErrorCode::ContextLengthExceeded,
+            "model_not_found" => ApiErrorCode::ModelNotFound,
+            "server_error" => ApiErrorCode::ServerError,
+            _ => ApiErrorCode::Other(s.to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiErrorEnvelope {
+    error: ApiErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    message: Option<String>,
+    r#type: Option<String>,
+    param: Option<String>,
+    code: Option<Value>,
 }
 
 impl std::fmt::Display for OpenApiError {
@@ -52,6 +221,20 @@ impl std::fmt::Display for OpenApiError {
             OpenApiError::ClientError(val) => {
                 write!(f, "ClientError: {}", val)
             }
+            OpenApiError::InvalidToolArguments(name, err) => {
+                write!(f, "Invalid arguments for tool {}: {}", name, err)
+            }
+            OpenApiError::Refusal(refusal) => write!(f, "The model refused: {}", refusal),
+            OpenApiError::SchemaViolation(err) => {
+                write!(f, "Reply does not match the response schema: {}", err)
+            }
+            OpenApiError::Api(err) => {
+                write!(f, "ApiError ({}): {}", err.status, err.message)?;
+                if let Some(request_id) = &err.request_id {
+                    write!(f, " [request id: {}]", request_id)?;
+                }
+                Ok(())
+            }
 
             _ => write!(f, "OpenApiError has occurred."),
         }
@@ -89,3 +272,39 @@ impl From<serde_json::Error> for OpenApiError {
         OpenApiError::SerdeJsonError(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_error_envelope() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", "req_123".parse().unwrap());
+        let body = br#"{"error": {"message": "Incorrect API key provided", "type": "invalid_request_error", "param": null, "code": "invalid_api_key"}}"#;
+
+        match OpenApiError::from_response(StatusCode::UNAUTHORIZED, &headers, body) {
+            OpenApiError::Api(err) => {
+                assert_eq!(err.get_status(), StatusCode::UNAUTHORIZED);
+                assert_eq!(err.get_request_id().unwrap(), "req_123");
+                assert_eq!(err.get_code(), &ApiErrorCode::InvalidApiKey);
+                assert_eq!(err.get_error_type().unwrap(), "invalid_request_error");
+                assert!(err.get_param().is_none());
+            }
+            other => panic!("Expected OpenApiError::Api, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_non_json_body() {
+        let body = b"upstream connect error";
+        match OpenApiError::from_response(StatusCode::BAD_GATEWAY, &HeaderMap::new(), body) {
+            OpenApiError::Api(err) => {
+                assert_eq!(err.get_message(), "upstream connect error");
+                assert_eq!(err.get_code(), &ApiErrorCode::Unknown);
+                assert!(err.get_request_id().is_none());
+            }
+            other => panic!("Expected OpenApiError::Api, found {:?}", other),
+        }
+    }
+}
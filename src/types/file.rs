@@ -1,12 +1,15 @@
-use crate::networking::Networking;
+#[cfg(feature = "async")]
+use crate::networking::{AsyncFileActions, AsyncNetworking};
+#[cfg(feature = "blocking")]
+use crate::networking::{FileActions, Networking};
 use crate::types::common::Identifiable;
 use crate::types::error::OpenApiError;
 
-use crate::{impl_ref, strip_edges};
+use crate::impl_ref;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct File {
     id: String,
     bytes: u64,
@@ -34,9 +37,15 @@ impl FileBuilder {
         let file = file.into();
         Self { file, purpose }
     }
+    #[cfg(feature = "blocking")]
     pub fn build(self, networking: &Networking) -> Result<File, OpenApiError> {
         networking.upload_file(self.file, self.purpose)
     }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(self, networking: &AsyncNetworking) -> Result<File, OpenApiError> {
+        networking.upload_file(self.file, self.purpose).await
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
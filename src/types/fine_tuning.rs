@@ -1,7 +1,13 @@
 use crate::common::Identifiable;
+#[cfg(feature = "blocking")]
+use crate::error::OpenApiError;
+#[cfg(feature = "blocking")]
 use crate::networking::Networking;
-use serde::Serialize;
+#[cfg(feature = "blocking")]
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
 
+#[derive(Deserialize, Debug, Clone)]
 pub struct FineTuningJob {
     id: String,
     created_at: i64,
@@ -31,8 +37,8 @@ pub struct FineTuningJobBuilder {
 impl FineTuningJobBuilder {
     pub fn new<M: Identifiable, TF: Identifiable>(model: M, training_file_id: TF) -> Self {
         Self {
-            model,
-            training_file: training_file_id,
+            model: model.get_identifier(),
+            training_file: training_file_id.get_identifier(),
             ..Self::default()
         }
     }
@@ -52,11 +58,18 @@ impl FineTuningJobBuilder {
         self
     }
 
-    pub fn build(&self, networking: &Networking) -> FineTuningJob {
-        todo!()
+    #[cfg(feature = "blocking")]
+    pub fn build(&self, networking: &Networking) -> Result<FineTuningJob, OpenApiError> {
+        networking.send_and_convert(
+            Method::POST,
+            String::from("fine_tuning/jobs"),
+            Some(serde_json::to_value(self)?),
+            None,
+        )
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
 pub struct FineTuningJobEvent {
     id: String,
     created_at: i64,
@@ -65,12 +78,14 @@ pub struct FineTuningJobEvent {
     object: String,
 }
 
+#[derive(Deserialize, Debug, Clone)]
 pub struct FineTuningError {
     code: String,
     message: String,
     param: Option<String>,
 }
 
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct HyperParams {
     n_epochs: Option<String>,
     batch_size: Option<String>,
@@ -1,10 +1,14 @@
 use crate::impl_ref;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::path::Path;
 
-use crate::networking::Networking;
-use crate::types::common::{Identifiable, ToolCalls};
+#[cfg(feature = "async")]
+use crate::networking::{AsyncMessageActions, AsyncNetworking};
+#[cfg(feature = "blocking")]
+use crate::networking::{MessageActions, Networking};
+use crate::types::common::{Identifiable, ToolCalls, Tools};
 use crate::types::error::OpenApiError;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,10 +21,19 @@ pub struct Message {
     content: Vec<MessageContent>,
     assistant_id: Option<String>,
     run_id: Option<String>,
+    #[serde(default)]
+    attachments: Option<Vec<Attachment>>,
+    #[serde(default)]
     file_ids: Vec<String>,
     metadata: HashMap<String, String>,
 }
 
+impl Message {
+    pub fn get_attachments(&self) -> &[Attachment] {
+        self.attachments.as_deref().unwrap_or_default()
+    }
+}
+
 impl Identifiable for Message {
     fn get_identifier(&self) -> String {
         self.id.clone()
@@ -35,7 +48,7 @@ pub struct MessageBuilder {
     role: MessageRole,
     content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    file_ids: Option<Vec<String>>,
+    attachments: Option<Vec<Attachment>>,
     metadata: Option<HashMap<String, String>>,
 }
 impl MessageBuilder {
@@ -77,35 +90,89 @@ impl MessageBuilder {
             thread_id,
             role,
             content,
-            file_ids: None,
+            attachments: None,
             metadata: None,
         })
     }
 
-    pub fn with_file_ids(mut self, file_ids: Vec<String>) -> Self {
-        self.file_ids = Some(file_ids);
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = Some(attachments);
         self
     }
 
-    pub fn add_file_id<I: Identifiable>(mut self, file_id: I) -> Self {
-        let file_id = file_id.get_identifier();
-        match &mut self.file_ids {
-            Some(file_ids) => file_ids.push(file_id),
-            None => self.file_ids = Some(vec![file_id]),
+    pub fn add_attachment(mut self, attachment: Attachment) -> Self {
+        match &mut self.attachments {
+            Some(attachments) => attachments.push(attachment),
+            None => self.attachments = Some(vec![attachment]),
         }
         self
     }
 
+    /// Attaches the files for file search, the v2 equivalent of the v1 `file_ids`.
+    #[deprecated(note = "file_ids was removed in Assistants v2, use `with_attachments`")]
+    pub fn with_file_ids(self, file_ids: Vec<String>) -> Self {
+        let attachments = file_ids
+            .into_iter()
+            .map(|file_id| Attachment::new(file_id, vec![Tools::file_search()]))
+            .collect();
+        self.with_attachments(attachments)
+    }
+
+    /// Attaches a file for file search, the v2 equivalent of the v1 `file_ids`.
+    #[deprecated(note = "file_ids was removed in Assistants v2, use `add_attachment`")]
+    pub fn add_file_id<I: Identifiable>(self, file_id: I) -> Self {
+        self.add_attachment(Attachment::new(file_id, vec![Tools::file_search()]))
+    }
+
     pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
         self.metadata = Some(metadata);
         self
     }
 
+    #[cfg(feature = "blocking")]
     pub fn build(&self, networking: &Networking) -> Result<Message, OpenApiError> {
         networking.create_message(self, &self.thread_id)
     }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(&self, networking: &AsyncNetworking) -> Result<Message, OpenApiError> {
+        networking.create_message(self, &self.thread_id).await
+    }
+}
+
+/// `Attachment` is a file attached to a message in Assistants v2, along with the tools it is
+/// made available to.
+///
+/// # Fields
+///
+/// * `file_id: String` - The identifier of the attached file.
+/// * `tools: Vec<Tools>` - The tools the file is added to, e.g. `Tools::file_search()`.
+///
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attachment {
+    file_id: String,
+    #[serde(default)]
+    tools: Vec<Tools>,
+}
+
+impl Attachment {
+    pub fn new<I: Identifiable>(file_id: I, tools: Vec<Tools>) -> Self {
+        Self {
+            file_id: file_id.get_identifier(),
+            tools,
+        }
+    }
+
+    pub fn get_file_id(&self) -> &str {
+        &self.file_id
+    }
+
+    pub fn get_tools(&self) -> &Vec<Tools> {
+        &self.tools
+    }
 }
 
+/// `MessageFile` is a file attached to a message, only available in Assistants v1.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MessageFile {
     id: String,
@@ -121,6 +188,7 @@ pub enum MessageRole {
     #[default]
     User,
     Assistant,
+    Tool,
 }
 
 impl From<&str> for MessageRole {
@@ -129,6 +197,7 @@ impl From<&str> for MessageRole {
             "system" => MessageRole::System,
             "user" => MessageRole::User,
             "assistant" => MessageRole::Assistant,
+            "tool" => MessageRole::Tool,
             _ => MessageRole::User,
         }
     }
@@ -140,10 +209,11 @@ impl From<&str> for MessageRole {
 pub enum MessageContent {
     Text(TextContent),
     ImageFile(ImageContent),
+    ImageUrl(ImageUrlContent),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct TextContent {
+pub struct TextContent {
     r#type: String,
     text: TextValue,
 }
@@ -155,7 +225,7 @@ struct TextValue {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct ImageContent {
+pub struct ImageContent {
     r#type: String,
     image_file: ImageLocation,
 }
@@ -163,6 +233,15 @@ struct ImageContent {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct ImageLocation {
     file_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<ImageDetail>,
+}
+
+/// An image referenced by url, only available in Assistants v2.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageUrlContent {
+    r#type: String,
+    image_url: ImageUrl,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -184,7 +263,9 @@ struct FileCitation {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct FileCitationLocation {
     file_id: String,
-    quote: String,
+    /// Only sent by Assistants v1.
+    #[serde(default)]
+    quote: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -201,7 +282,7 @@ struct FilePathLocation {
     file_id: String,
 }
 
-/** ---- General Message ----
+/* ---- General Message ----
 Used for representing the various message objects
 
 */
@@ -209,7 +290,10 @@ Used for representing the various message objects
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct GeneralMessage {
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<CompletionContent>,
+    /// Set instead of `content` when the model refuses a request with a response schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refusal: Option<String>,
     role: MessageRole,
     #[serde(skip_serializing_if = "Option::is_none")]
     name: Option<String>,
@@ -220,12 +304,56 @@ pub struct GeneralMessage {
     #[serde(skip_serializing_if = "Option::is_none")]
     file_ids: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    attachments: Option<Vec<Attachment>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     metadata: Option<HashMap<String, String>>,
 }
 
 impl GeneralMessage {
+    /// A user message for a thread, e.g. in `ThreadBuilder::add_message` or
+    /// `RunBuilder::add_message`.
+    pub fn new_user<C: Into<String>>(content: C) -> Self {
+        Self {
+            content: Some(CompletionContent::Text(content.into())),
+            role: MessageRole::User,
+            ..GeneralMessage::default()
+        }
+    }
+
+    /// A message returned by the model, e.g. as rebuilt by `ChatCompletionAccumulator`.
+    pub(crate) fn new_reply(
+        role: MessageRole,
+        content: Option<String>,
+        tool_calls: Option<Vec<ToolCalls>>,
+    ) -> Self {
+        Self {
+            content: content.map(CompletionContent::Text),
+            role,
+            tool_calls,
+            ..GeneralMessage::default()
+        }
+    }
+
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = Some(attachments);
+        self
+    }
+
+    /// Returns the text of the content, where the text parts are joined.
     pub fn get_content(&self) -> Option<String> {
-        self.content.clone()
+        self.content.as_ref().map(CompletionContent::get_text)
+    }
+
+    pub fn get_completion_content(&self) -> Option<&CompletionContent> {
+        self.content.as_ref()
+    }
+
+    pub fn get_refusal(&self) -> Option<&str> {
+        self.refusal.as_deref()
+    }
+
+    pub fn get_tool_calls(&self) -> &[ToolCalls] {
+        self.tool_calls.as_deref().unwrap_or_default()
     }
 }
 
@@ -241,7 +369,7 @@ pub enum CompletionMessage {
 impl CompletionMessage {
     pub fn new_system(content: String, name: Option<String>) -> CompletionMessage {
         CompletionMessage::SystemMessage(GeneralMessage {
-            content: Some(content),
+            content: Some(CompletionContent::Text(content)),
             role: "system".into(),
             name,
             ..GeneralMessage::default()
@@ -250,7 +378,17 @@ impl CompletionMessage {
 
     pub fn new_user(content: String, name: Option<String>) -> CompletionMessage {
         CompletionMessage::UserMessage(GeneralMessage {
-            content: Some(content),
+            content: Some(CompletionContent::Text(content)),
+            role: "user".into(),
+            name,
+            ..GeneralMessage::default()
+        })
+    }
+
+    /// A user message mixing text and images, for models accepting image input.
+    pub fn new_user_parts(parts: Vec<ContentPart>, name: Option<String>) -> CompletionMessage {
+        CompletionMessage::UserMessage(GeneralMessage {
+            content: Some(CompletionContent::Parts(parts)),
             role: "user".into(),
             name,
             ..GeneralMessage::default()
@@ -263,7 +401,7 @@ impl CompletionMessage {
         tool_calls: Option<Vec<ToolCalls>>,
     ) -> CompletionMessage {
         CompletionMessage::AssistantMessage(GeneralMessage {
-            content,
+            content: content.map(CompletionContent::Text),
             role: "assistant".into(),
             name,
             tool_calls,
@@ -273,10 +411,257 @@ impl CompletionMessage {
 
     pub fn new_tool(content: String, tool_call_id: Option<String>) -> CompletionMessage {
         CompletionMessage::ToolMessage(GeneralMessage {
-            content: Some(content),
+            content: Some(CompletionContent::Text(content)),
             role: "tool".into(),
             tool_call_id,
             ..GeneralMessage::default()
         })
     }
 }
+
+/// The content of a chat completion message, either text or a list of parts mixing text and
+/// images.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CompletionContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl CompletionContent {
+    /// Returns the text, joining the text parts with newlines.
+    pub fn get_text(&self) -> String {
+        match self {
+            CompletionContent::Text(text) => text.clone(),
+            CompletionContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    ContentPart::ImageUrl { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// A part of `CompletionContent`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+impl ContentPart {
+    pub fn text<T: Into<String>>(text: T) -> Self {
+        ContentPart::Text { text: text.into() }
+    }
+
+    pub fn image(image_url: ImageUrl) -> Self {
+        ContentPart::ImageUrl { image_url }
+    }
+}
+
+/// An image sent to the model, either by url or inlined as a base64 `data:` url.
+///
+/// # Examples
+/// ```ignore
+/// let message = CompletionMessage::new_user_parts(
+///     vec![
+///         ContentPart::text("What is in this image?"),
+///         ContentPart::image(ImageUrl::from_file("photo.jpg")?.with_detail(ImageDetail::Low)),
+///     ],
+///     None,
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageUrl {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<ImageDetail>,
+}
+
+/// The resolution the model views an image at, `Auto` unless set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    Auto,
+    Low,
+    High,
+}
+
+impl ImageUrl {
+    pub fn new<U: Into<String>>(url: U) -> Self {
+        Self {
+            url: url.into(),
+            detail: None,
+        }
+    }
+
+    /// Inlines a PNG, JPEG, WebP or GIF image as a data url, the format being detected from the
+    /// leading bytes of the image.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OpenApiError> {
+        let mime_type = image_mime_type(bytes).ok_or_else(|| {
+            OpenApiError::RestrictedValue("Images must be in PNG, JPEG, WebP or GIF format".into())
+        })?;
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        Ok(ImageUrl::new(format!("data:{};base64,{}", mime_type, data)))
+    }
+
+    /// Reads a local image and inlines it as a data url, see `from_bytes`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, OpenApiError> {
+        ImageUrl::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn with_detail(mut self, detail: ImageDetail) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_detail(&self) -> Option<ImageDetail> {
+        self.detail
+    }
+}
+
+/// Detects the image formats accepted by the api from their magic numbers.
+fn image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_v2_message_deserialized() {
+        let message: Message = serde_json::from_value(json!({
+            "id": "msg_abc123",
+            "object": "thread.message",
+            "created_at": 1713226573,
+            "assistant_id": "asst_abc123",
+            "thread_id": "thread_abc123",
+            "run_id": "run_abc123",
+            "status": "completed",
+            "incomplete_details": null,
+            "completed_at": 1713226575,
+            "incomplete_at": null,
+            "role": "assistant",
+            "content": [
+                {
+                    "type": "text",
+                    "text": {
+                        "value": "Revenue grew 12% year over year【4:0†report.pdf】.",
+                        "annotations": [
+                            {
+                                "type": "file_citation",
+                                "text": "【4:0†report.pdf】",
+                                "start_index": 36,
+                                "end_index": 52,
+                                "file_citation": {"file_id": "file-abc123"}
+                            }
+                        ]
+                    }
+                },
+                {"type": "image_file", "image_file": {"file_id": "file-img123", "detail": "auto"}},
+                {"type": "image_url", "image_url": {"url": "https://example.com/chart.png", "detail": "high"}}
+            ],
+            "attachments": [{"file_id": "file-abc123", "tools": [{"type": "file_search"}]}],
+            "metadata": {}
+        }))
+        .unwrap();
+
+        assert_eq!(message.get_attachments().len(), 1);
+        match &message.content[0] {
+            MessageContent::Text(text) => match &text.text.annotations[0] {
+                Annotations::FileCitation(citation) => {
+                    assert_eq!(citation.file_citation.file_id, "file-abc123");
+                    assert_eq!(citation.file_citation.quote, None);
+                }
+                other => panic!("unexpected annotation {:?}", other),
+            },
+            other => panic!("unexpected content {:?}", other),
+        }
+        assert!(matches!(message.content[1], MessageContent::ImageFile(_)));
+        match &message.content[2] {
+            MessageContent::ImageUrl(image) => {
+                assert_eq!(image.image_url.detail, Some(ImageDetail::High))
+            }
+            other => panic!("unexpected content {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_content_parts_serialized() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00];
+        let message = CompletionMessage::new_user_parts(
+            vec![
+                ContentPart::text("What is in this image?"),
+                ContentPart::image(
+                    ImageUrl::from_bytes(&png)
+                        .unwrap()
+                        .with_detail(ImageDetail::Low),
+                ),
+            ],
+            None,
+        );
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({"role": "user", "content": [
+                {"type": "text", "text": "What is in this image?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgoA", "detail": "low"}},
+            ]})
+        );
+        assert_eq!(
+            serde_json::to_value(CompletionMessage::new_user("Hi".into(), None)).unwrap(),
+            json!({"role": "user", "content": "Hi"})
+        );
+    }
+
+    #[test]
+    fn test_image_mime_type() {
+        assert_eq!(image_mime_type(b"\xFF\xD8\xFF\xE0"), Some("image/jpeg"));
+        assert_eq!(image_mime_type(b"GIF89a\x01\x00"), Some("image/gif"));
+        assert_eq!(
+            image_mime_type(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(image_mime_type(b"%PDF-1.7"), None);
+        assert!(ImageUrl::from_bytes(b"BM").is_err());
+    }
+
+    #[test]
+    fn test_content_text_read_back() {
+        let message: GeneralMessage = serde_json::from_value(json!({
+            "role": "user",
+            "content": [{"type": "text", "text": "Hello"}, {"type": "image_url", "image_url": {"url": "https://example.com/a.png"}}],
+        }))
+        .unwrap();
+        assert_eq!(message.get_content().unwrap(), "Hello");
+        assert!(matches!(
+            message.get_completion_content(),
+            Some(CompletionContent::Parts(parts)) if parts.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_text_parts_joined_by_newlines() {
+        let content = CompletionContent::Parts(vec![
+            ContentPart::text("First line."),
+            ContentPart::image(ImageUrl::new("https://example.com/a.png")),
+            ContentPart::text("Second line."),
+        ]);
+        assert_eq!(content.get_text(), "First line.\nSecond line.");
+    }
+}
@@ -1,10 +1,9 @@
 use crate::impl_ref;
-use crate::networking::Networking;
 use serde::{Deserialize, Serialize};
 
 use crate::types::common::Identifiable;
 
-#[derive(Default, Serialize, Deserialize, Clone)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct Model {
     id: String,
     created: i64,
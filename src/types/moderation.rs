@@ -30,7 +30,7 @@ pub struct ModerationRecord {
 
 impl ModerationRecord {
     pub fn is_flagged(&self) -> bool {
-        return self.flagged;
+        self.flagged
     }
 }
 
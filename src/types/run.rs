@@ -1,13 +1,15 @@
-use crate::networking::Networking;
-use crate::types::common::{Identifiable, ToolCalls, Tools, Usage};
+#[cfg(feature = "async")]
+use crate::networking::{AsyncMessageActions, AsyncNetworking, AsyncRunActions};
+#[cfg(feature = "blocking")]
+use crate::networking::{MessageActions, Networking, RunActions};
+use crate::types::common::{Identifiable, ToolCalls, ToolChoice, Tools, Usage};
 use crate::types::error::OpenApiError;
 
+use crate::chat::ResponseFormat;
 use crate::impl_ref;
-use crate::message::Message;
+use crate::message::{GeneralMessage, Message};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::hash::Hash;
-use std::ops::Deref;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Run {
@@ -27,21 +29,50 @@ pub struct Run {
     model: String,
     instructions: String,
     tools: Vec<Tools>,
+    #[serde(default)]
     file_ids: Vec<String>,
     metadata: HashMap<String, String>,
     usage: Option<Usage>,
+    #[serde(default)]
+    incomplete_details: Option<IncompleteDetails>,
+    #[serde(default)]
+    temperature: Option<f64>,
+    #[serde(default)]
+    top_p: Option<f64>,
+    #[serde(default)]
+    max_prompt_tokens: Option<u32>,
+    #[serde(default)]
+    max_completion_tokens: Option<u32>,
+    #[serde(default)]
+    truncation_strategy: Option<TruncationStrategy>,
+    #[serde(default)]
+    parallel_tool_calls: Option<bool>,
 }
 
 impl Run {
-    pub fn retrieve_self(mut self, networking: &Networking) -> Result<Run, OpenApiError> {
+    #[cfg(feature = "blocking")]
+    pub fn retrieve_self(self, networking: &Networking) -> Result<Run, OpenApiError> {
         // TODO: Rethink name?
         // TODO: Is passing networking this way the best idea?
         networking.retrieve_run(self.thread_id.clone(), self.id.clone())
     }
 
+    #[cfg(feature = "async")]
+    pub async fn retrieve_self_async(
+        self,
+        networking: &AsyncNetworking,
+    ) -> Result<Run, OpenApiError> {
+        networking
+            .retrieve_run(self.thread_id.clone(), self.id.clone())
+            .await
+    }
+
+    #[cfg(feature = "blocking")]
     pub fn retrieve_first_message(&self, networking: &Networking) -> Result<Message, OpenApiError> {
-        let messages = networking.list_messages(self.thread_id.clone()).unwrap();
-        if messages.get_data_vec().len() < 1 {
+        let messages = networking
+            .list_messages(self.thread_id.clone(), None)
+            .unwrap();
+        if messages.get_data_vec().is_empty() {
             // TODO: Change to OperationalError when completed
             return Err(OpenApiError::ClientError("Run contains no messages".into()));
         }
@@ -49,15 +80,33 @@ impl Run {
         Ok(message.clone())
     }
 
+    #[cfg(feature = "async")]
+    pub async fn retrieve_first_message_async(
+        &self,
+        networking: &AsyncNetworking,
+    ) -> Result<Message, OpenApiError> {
+        let messages = networking
+            .list_messages(self.thread_id.clone(), None)
+            .await?;
+        match messages.get_data_vec().first() {
+            Some(message) => Ok(message.clone()),
+            None => Err(OpenApiError::ClientError("Run contains no messages".into())),
+        }
+    }
+
     pub fn retrieve_status(&self) -> RunStatus {
         self.status.clone()
     }
 
+    /// Returns why the run ended with `RunStatus::Incomplete`, e.g. `max_completion_tokens`.
+    pub fn get_incomplete_reason(&self) -> Option<&str> {
+        self.incomplete_details
+            .as_ref()
+            .map(|details| details.reason.as_str())
+    }
+
     pub fn is_complete(&self) -> bool {
-        match self.status {
-            RunStatus::Completed => true,
-            _ => false,
-        }
+        matches!(self.status, RunStatus::Completed)
     }
 }
 
@@ -76,8 +125,27 @@ pub struct RunBuilder {
     model: Option<String>,
     instructions: Option<String>,
     additional_instructions: Option<String>,
-    tools: Vec<()>, //TODO
+    #[serde(skip_serializing_if = "Option::is_none")]
+    additional_messages: Option<Vec<GeneralMessage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Tools>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
     metadata: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_prompt_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncation_strategy: Option<TruncationStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
 }
 
 impl RunBuilder {
@@ -120,9 +188,122 @@ impl RunBuilder {
         self
     }
 
+    /// Adds a message to the thread before the run starts.
+    pub fn add_message(mut self, message: GeneralMessage) -> Self {
+        match &mut self.additional_messages {
+            Some(messages) => messages.push(message),
+            None => self.additional_messages = Some(vec![message]),
+        }
+        self
+    }
+
+    /// Overrides the tools of the assistant for this run.
+    pub fn with_tools(mut self, tools: Vec<Tools>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Controls which tool is called. When the tools are overridden with `with_tools`, the choice
+    /// is checked against them once the run is created.
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f64) -> Result<Self, OpenApiError> {
+        match temperature {
+            0.0..=2.0 => {
+                self.temperature = Some(temperature);
+                Ok(self)
+            }
+            _ => Err(OpenApiError::RestrictedValue(
+                "Temperature must be between 0.0 and 2.0".into(),
+            )),
+        }
+    }
+
+    pub fn with_top_p(mut self, top_p: f64) -> Result<Self, OpenApiError> {
+        match top_p {
+            0.0..=1.0 => {
+                self.top_p = Some(top_p);
+                Ok(self)
+            }
+            _ => Err(OpenApiError::RestrictedValue(
+                "Top P must be between 0.0 and 1.0".into(),
+            )),
+        }
+    }
+
+    /// Caps the prompt tokens used across the run, after which it ends as incomplete.
+    pub fn with_max_prompt_tokens(mut self, max_prompt_tokens: u32) -> Self {
+        self.max_prompt_tokens = Some(max_prompt_tokens);
+        self
+    }
+
+    /// Caps the completion tokens used across the run, after which it ends as incomplete.
+    pub fn with_max_completion_tokens(mut self, max_completion_tokens: u32) -> Self {
+        self.max_completion_tokens = Some(max_completion_tokens);
+        self
+    }
+
+    pub fn with_truncation_strategy(mut self, truncation_strategy: TruncationStrategy) -> Self {
+        self.truncation_strategy = Some(truncation_strategy);
+        self
+    }
+
+    pub fn with_parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.parallel_tool_calls = Some(parallel_tool_calls);
+        self
+    }
+
+    /// Overrides the response format of the assistant for this run, see `ResponseFormat`.
+    pub fn with_response_format(
+        mut self,
+        response_format: ResponseFormat,
+    ) -> Result<Self, OpenApiError> {
+        response_format.validate()?;
+        self.response_format = Some(response_format);
+        Ok(self)
+    }
+
+    #[cfg(feature = "blocking")]
     pub fn build(&self, networking: &Networking) -> Result<Run, OpenApiError> {
+        self.validate()?;
         networking.create_run(self, &self.thread_id)
     }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(&self, networking: &AsyncNetworking) -> Result<Run, OpenApiError> {
+        self.validate()?;
+        networking.create_run(self, &self.thread_id).await
+    }
+
+    fn validate(&self) -> Result<(), OpenApiError> {
+        match (&self.tool_choice, &self.tools) {
+            (Some(tool_choice), Some(tools)) => tool_choice.validate(tools),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `TruncationStrategy` controls how the thread is truncated to fit the context window of a run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// Messages in the middle of the thread are dropped to fit the context window.
+    Auto,
+    /// Only the most recent `last_messages` messages are used.
+    LastMessages { last_messages: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IncompleteDetails {
+    reason: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -162,6 +343,7 @@ pub enum RunStatus {
     Cancelled,
     Failed,
     Completed,
+    Incomplete,
     Expired,
 }
 
@@ -176,6 +358,7 @@ pub struct LastError {
 pub enum LastErrorCode {
     ServerError,
     RateLimitExceeded,
+    InvalidPrompt,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -195,7 +378,7 @@ pub enum RequiredActionType {
     SubmitToolOutput,
 }
 
-/** ---- Run Tests ---- */
+/* ---- Run Tests ---- */
 
 #[cfg(test)]
 mod tests {
@@ -203,7 +386,40 @@ mod tests {
 
     #[test]
     fn test_run_builder() {
-        //let run = RunBuilder::new_with_thread("asst_SJVM5rueqSA5KWXbOsvR2EO5".into());
-        todo!()
+        let run = RunBuilder::new("thread_abc123", "asst_SJVM5rueqSA5KWXbOsvR2EO5")
+            .add_message(GeneralMessage::new_user("Summarise the attached report"))
+            .with_max_completion_tokens(500)
+            .with_truncation_strategy(TruncationStrategy::LastMessages { last_messages: 10 })
+            .with_parallel_tool_calls(false)
+            .with_response_format(ResponseFormat::JsonObject)
+            .unwrap();
+        let body = serde_json::to_value(&run).unwrap();
+        assert_eq!(body["additional_messages"][0]["role"], "user");
+        assert_eq!(body["max_completion_tokens"], 500);
+        assert_eq!(
+            body["truncation_strategy"],
+            serde_json::json!({"type": "last_messages", "last_messages": 10})
+        );
+        assert!(body.get("tools").is_none());
+        assert_eq!(body["response_format"]["type"], "json_object");
+        assert!(RunBuilder::new_with_thread("asst_abc123")
+            .with_top_p(1.5)
+            .is_err());
+    }
+
+    #[test]
+    fn test_tool_choice_checked_against_overridden_tools() {
+        let tools: Vec<Tools> = serde_json::from_value(serde_json::json!([
+            {"type": "function", "function": {"name": "get_weather", "description": "Weather"}}
+        ]))
+        .unwrap();
+        let run = || {
+            RunBuilder::new("thread_abc123", "asst_abc123")
+                .with_tool_choice(ToolChoice::function("get_weather"))
+        };
+        // The tools of the assistant are unknown, so only overridden tools are checked
+        assert!(run().validate().is_ok());
+        assert!(run().with_tools(tools).validate().is_ok());
+        assert!(run().with_tools(Vec::new()).validate().is_err());
     }
 }
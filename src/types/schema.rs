@@ -0,0 +1,507 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::types::common::{ToolCalls, ToolFunction, Tools};
+use crate::types::error::OpenApiError;
+
+#[cfg(feature = "derive")]
+pub use rust_open_ai_derive::{tool, ToolArgs, ToolSchema};
+
+/// `JsonSchema` describes the JSON a function tool accepts as its arguments, sent as the
+/// `parameters` of a `ToolFunction`.
+///
+/// The common keywords are typed, while any other keyword (e.g. `minimum` or `format`) can be set
+/// with `with_keyword` and is kept as is when a schema is read back from the api.
+///
+/// # Examples
+/// ```ignore
+/// let parameters = JsonSchema::object()
+///     .with_property("city", JsonSchema::string().with_description("The city, e.g. Paris"))
+///     .with_property("unit", JsonSchema::enumeration(["celsius", "fahrenheit"]))
+///     .with_optional_property("days", JsonSchema::integer());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchema {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    r#type: Option<SchemaTypes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    enumeration: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<BTreeMap<String, JsonSchema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    items: Option<Box<JsonSchema>>,
+    #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
+    any_of: Option<Vec<JsonSchema>>,
+    #[serde(
+        rename = "additionalProperties",
+        skip_serializing_if = "Option::is_none"
+    )]
+    additional_properties: Option<bool>,
+    #[serde(flatten)]
+    keywords: BTreeMap<String, Value>,
+}
+
+/// The JSON types a `JsonSchema` can accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Array,
+    Object,
+    Null,
+}
+
+/// The `type` keyword, which is either a single type or a list of types.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SchemaTypes {
+    One(SchemaType),
+    Many(Vec<SchemaType>),
+}
+
+impl JsonSchema {
+    fn of_type(r#type: SchemaType) -> Self {
+        Self {
+            r#type: Some(SchemaTypes::One(r#type)),
+            ..JsonSchema::default()
+        }
+    }
+
+    pub fn string() -> Self {
+        JsonSchema::of_type(SchemaType::String)
+    }
+
+    pub fn number() -> Self {
+        JsonSchema::of_type(SchemaType::Number)
+    }
+
+    pub fn integer() -> Self {
+        JsonSchema::of_type(SchemaType::Integer)
+    }
+
+    pub fn boolean() -> Self {
+        JsonSchema::of_type(SchemaType::Boolean)
+    }
+
+    pub fn null() -> Self {
+        JsonSchema::of_type(SchemaType::Null)
+    }
+
+    /// An object without properties, which are added with `with_property`. Properties not
+    /// declared are rejected, as required by `strict` mode.
+    pub fn object() -> Self {
+        Self {
+            properties: Some(BTreeMap::new()),
+            required: Some(Vec::new()),
+            additional_properties: Some(false),
+            ..JsonSchema::of_type(SchemaType::Object)
+        }
+    }
+
+    /// An array whose every element matches `items`.
+    pub fn array(items: JsonSchema) -> Self {
+        Self {
+            items: Some(Box::new(items)),
+            ..JsonSchema::of_type(SchemaType::Array)
+        }
+    }
+
+    /// A string restricted to `values`.
+    pub fn enumeration<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            enumeration: Some(
+                values
+                    .into_iter()
+                    .map(|value| Value::String(value.into()))
+                    .collect(),
+            ),
+            ..JsonSchema::string()
+        }
+    }
+
+    /// A value matching at least one of `schemas`.
+    pub fn any_of(schemas: Vec<JsonSchema>) -> Self {
+        Self {
+            any_of: Some(schemas),
+            ..JsonSchema::default()
+        }
+    }
+
+    /// Also accepts `null`, the way optional fields are expressed in `strict` mode.
+    pub fn nullable(self) -> Self {
+        JsonSchema::any_of(vec![self, JsonSchema::null()])
+    }
+
+    pub fn with_description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds a required property to an object.
+    pub fn with_property<N: Into<String>>(self, name: N, schema: JsonSchema) -> Self {
+        let name = name.into();
+        let mut schema = self.with_optional_property(name.clone(), schema);
+        let required = schema.required.get_or_insert_with(Vec::new);
+        if !required.contains(&name) {
+            required.push(name);
+        }
+        schema
+    }
+
+    /// Adds a property to an object which may be left out, which `strict` mode does not allow.
+    pub fn with_optional_property<N: Into<String>>(mut self, name: N, schema: JsonSchema) -> Self {
+        self.properties
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.into(), schema);
+        self
+    }
+
+    pub fn with_additional_properties(mut self, additional_properties: bool) -> Self {
+        self.additional_properties = Some(additional_properties);
+        self
+    }
+
+    /// Sets a keyword without a typed setter, e.g. `minimum` or `pattern`.
+    pub fn with_keyword<K: Into<String>>(mut self, keyword: K, value: Value) -> Self {
+        self.keywords.insert(keyword.into(), value);
+        self
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn get_properties(&self) -> Option<&BTreeMap<String, JsonSchema>> {
+        self.properties.as_ref()
+    }
+
+    pub fn get_required(&self) -> &[String] {
+        self.required.as_deref().unwrap_or_default()
+    }
+
+    /// Whether the schema describes an object, by its type or by listing properties.
+    pub(crate) fn is_object(&self) -> bool {
+        let is_object = match &self.r#type {
+            Some(SchemaTypes::One(r#type)) => *r#type == SchemaType::Object,
+            Some(SchemaTypes::Many(types)) => types.contains(&SchemaType::Object),
+            None => false,
+        };
+        is_object || self.properties.is_some()
+    }
+
+    /// Checks the restrictions of `strict` mode: every object lists all of its properties as
+    /// required and sets `additionalProperties` to false.
+    pub(crate) fn validate_strict(&self) -> Result<(), OpenApiError> {
+        self.validate_strict_at("parameters")
+    }
+
+    fn validate_strict_at(&self, path: &str) -> Result<(), OpenApiError> {
+        if self.is_object() {
+            if self.additional_properties != Some(false) {
+                return Err(OpenApiError::RestrictedValue(format!(
+                    "Strict schemas require additionalProperties to be false at {}",
+                    path
+                )));
+            }
+            let required = self.get_required();
+            for (name, property) in self.properties.iter().flatten() {
+                if !required.contains(name) {
+                    return Err(OpenApiError::RestrictedValue(format!(
+                        "Strict schemas require every property to be required, {}.{} is not",
+                        path, name
+                    )));
+                }
+                property.validate_strict_at(&format!("{}.{}", path, name))?;
+            }
+        }
+        if let Some(items) = &self.items {
+            items.validate_strict_at(&format!("{}[]", path))?;
+        }
+        for schema in self.any_of.iter().flatten() {
+            schema.validate_strict_at(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// `ToolSchema` describes the JSON representation of a type, so it can be used in the arguments of
+/// a tool. With the `derive` feature it can be derived for structs and for enums without fields.
+pub trait ToolSchema {
+    fn tool_schema() -> JsonSchema;
+}
+
+macro_rules! impl_tool_schema {
+    ($schema:ident => $($type:ty),+) => {
+        $(impl ToolSchema for $type {
+            fn tool_schema() -> JsonSchema {
+                JsonSchema::$schema()
+            }
+        })+
+    };
+}
+
+impl_tool_schema!(string => String, char);
+impl_tool_schema!(boolean => bool);
+impl_tool_schema!(integer => i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_tool_schema!(number => f32, f64);
+
+impl<T: ToolSchema> ToolSchema for Option<T> {
+    fn tool_schema() -> JsonSchema {
+        T::tool_schema().nullable()
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Vec<T> {
+    fn tool_schema() -> JsonSchema {
+        JsonSchema::array(T::tool_schema())
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Box<T> {
+    fn tool_schema() -> JsonSchema {
+        T::tool_schema()
+    }
+}
+
+/// `ToolArgs` is implemented by the arguments of a function tool, defining the tool and decoding
+/// the arguments of its calls. With the `derive` feature it is derived with `#[derive(ToolArgs)]`,
+/// or generated for a function with `#[tool]`.
+///
+/// # Examples
+/// ```ignore
+/// /// Returns the current weather
+/// #[derive(Deserialize, ToolArgs)]
+/// struct GetWeather {
+///     /// The city, e.g. Paris
+///     city: String,
+///     unit: Option<Unit>,
+/// }
+///
+/// let builder = builder.with_tools(vec![GetWeather::definition()?]);
+/// // ...
+/// let args = GetWeather::from_tool_call(&tool_call)?;
+/// ```
+pub trait ToolArgs: ToolSchema + DeserializeOwned {
+    /// The name of the tool.
+    const NAME: &'static str;
+    /// The description of the tool, empty if there is none.
+    const DESCRIPTION: &'static str;
+
+    /// Returns the tool with these arguments, in `strict` mode.
+    fn definition() -> Result<Tools, OpenApiError> {
+        Tools::function(
+            ToolFunction::new(Self::NAME)
+                .with_description(Self::DESCRIPTION)
+                .with_parameters(Self::tool_schema())
+                .with_strict(true),
+        )
+    }
+
+    /// Decodes the `arguments` of a call, as generated by the model.
+    fn from_arguments(arguments: &str) -> Result<Self, OpenApiError> {
+        serde_json::from_str(arguments)
+            .map_err(|err| OpenApiError::InvalidToolArguments(Self::NAME.to_string(), err))
+    }
+
+    /// Decodes the arguments of `tool_call`, which must be a call of this tool.
+    fn from_tool_call(tool_call: &ToolCalls) -> Result<Self, OpenApiError> {
+        let function = tool_call.get_function();
+        if function.get_name() != Self::NAME {
+            return Err(OpenApiError::ClientError(format!(
+                "Tool call {} is for {}, not {}",
+                tool_call.get_id(),
+                function.get_name(),
+                Self::NAME
+            )));
+        }
+        Self::from_arguments(function.get_arguments())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_serialized() {
+        let schema = JsonSchema::object()
+            .with_property(
+                "city",
+                JsonSchema::string().with_description("The city, e.g. Paris"),
+            )
+            .with_property("unit", JsonSchema::enumeration(["celsius", "fahrenheit"]))
+            .with_property(
+                "days",
+                JsonSchema::array(JsonSchema::integer().with_keyword("minimum", json!(0))),
+            );
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string", "description": "The city, e.g. Paris"},
+                    "unit": {"type": "string", "enum": ["celsius", "fahrenheit"]},
+                    "days": {"type": "array", "items": {"type": "integer", "minimum": 0}},
+                },
+                "required": ["city", "unit", "days"],
+                "additionalProperties": false,
+            })
+        );
+        assert!(schema.validate_strict().is_ok());
+    }
+
+    #[test]
+    fn test_strict_rejects_optional_property() {
+        let schema = JsonSchema::object().with_property(
+            "location",
+            JsonSchema::object().with_optional_property("zip", JsonSchema::string()),
+        );
+        assert!(schema.validate_strict().is_err());
+
+        let schema = JsonSchema::object().with_property(
+            "location",
+            JsonSchema::object().with_property("zip", JsonSchema::string().nullable()),
+        );
+        assert!(schema.validate_strict().is_ok());
+    }
+
+    #[test]
+    fn test_function_tool() {
+        use crate::types::common::{ToolFunction, Tools};
+
+        let parameters = JsonSchema::object().with_optional_property("city", JsonSchema::string());
+        let function = ToolFunction::new("get_weather")
+            .with_description("Returns the current weather")
+            .with_parameters(parameters);
+        let tool = Tools::function(function.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&tool).unwrap()["function"]["parameters"]["properties"]["city"],
+            json!({"type": "string"})
+        );
+        assert!(Tools::function(function.with_strict(true)).is_err());
+        assert!(Tools::function(ToolFunction::new("get weather")).is_err());
+    }
+
+    #[test]
+    fn test_schema_read_back() {
+        let value = json!({
+            "type": ["string", "null"],
+            "format": "date",
+        });
+        let schema: JsonSchema = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), value);
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive {
+        use super::*;
+        use crate::types::common::ToolCalls;
+
+        #[derive(Debug, PartialEq, Deserialize, ToolSchema)]
+        #[serde(rename_all = "lowercase")]
+        enum Unit {
+            Celsius,
+            #[serde(rename = "F")]
+            Fahrenheit,
+        }
+
+        /// Returns the current weather
+        #[derive(Debug, Deserialize, ToolArgs)]
+        struct GetWeather {
+            /// The city, e.g. Paris
+            city: String,
+            unit: Option<Unit>,
+            #[serde(rename = "days")]
+            forecast_days: Vec<u8>,
+        }
+
+        /// Adds two numbers
+        #[tool(name = "add")]
+        fn add_numbers(
+            /// The first number
+            a: i64,
+            b: i64,
+        ) -> i64 {
+            a + b
+        }
+
+        #[test]
+        fn test_derived_definition() {
+            let tool = GetWeather::definition().unwrap();
+            assert_eq!(
+                serde_json::to_value(&tool).unwrap()["function"],
+                json!({
+                    "name": "get_weather",
+                    "description": "Returns the current weather",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {
+                            "city": {"type": "string", "description": "The city, e.g. Paris"},
+                            "unit": {"anyOf": [
+                                {"type": "string", "enum": ["celsius", "F"]},
+                                {"type": "null"},
+                            ]},
+                            "days": {"type": "array", "items": {"type": "integer"}},
+                        },
+                        "required": ["city", "unit", "days"],
+                        "additionalProperties": false,
+                    },
+                    "strict": true,
+                })
+            );
+        }
+
+        #[test]
+        fn test_decode_arguments() {
+            let args = GetWeather::from_arguments(r#"{"city": "Paris", "unit": "F", "days": [1]}"#)
+                .unwrap();
+            assert_eq!(args.city, "Paris");
+            assert_eq!(args.unit, Some(Unit::Fahrenheit));
+            assert_eq!(args.forecast_days, vec![1]);
+
+            let err = GetWeather::from_arguments(r#"{"unit": null, "days": []}"#).unwrap_err();
+            assert!(err
+                .to_string()
+                .starts_with("Invalid arguments for tool get_weather: missing field `city`"));
+
+            let tool_call: ToolCalls = serde_json::from_value(json!({
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "add", "arguments": "{}"},
+            }))
+            .unwrap();
+            assert!(matches!(
+                GetWeather::from_tool_call(&tool_call),
+                Err(OpenApiError::ClientError(_))
+            ));
+        }
+
+        #[test]
+        fn test_tool_function() {
+            let tool = AddNumbersArgs::definition().unwrap();
+            let function = tool.get_function().unwrap();
+            assert_eq!(function.get_name(), "add");
+            assert_eq!(function.get_description(), "Adds two numbers");
+            assert_eq!(
+                function.get_parameters().unwrap().get_properties().unwrap()["a"].get_description(),
+                Some("The first number")
+            );
+            let args = AddNumbersArgs::from_arguments(r#"{"a": 1, "b": 2}"#).unwrap();
+            assert_eq!(args.call(), 3);
+        }
+    }
+}
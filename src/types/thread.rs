@@ -1,6 +1,9 @@
 use crate::impl_ref;
-use crate::networking::Networking;
-use crate::types::common::{ApiList, Identifiable};
+#[cfg(feature = "async")]
+use crate::networking::{AsyncNetworking, AsyncThreadActions};
+#[cfg(feature = "blocking")]
+use crate::networking::{Networking, ThreadActions};
+use crate::types::common::{Identifiable, ToolResources};
 use crate::types::error::OpenApiError;
 use crate::types::message::GeneralMessage;
 use serde::{Deserialize, Serialize};
@@ -11,9 +14,17 @@ pub struct Thread {
     id: String,
     object: String,
     created_at: i64,
+    #[serde(default)]
+    tool_resources: Option<ToolResources>,
     metadata: HashMap<String, String>,
 }
 
+impl Thread {
+    pub fn get_tool_resources(&self) -> Option<&ToolResources> {
+        self.tool_resources.as_ref()
+    }
+}
+
 impl Identifiable for Thread {
     fn get_identifier(&self) -> String {
         self.id.clone()
@@ -24,6 +35,8 @@ impl_ref!(Thread, Identifiable);
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct ThreadBuilder {
     messages: Option<Vec<GeneralMessage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_resources: Option<ToolResources>,
     metadata: Option<HashMap<String, String>>,
 }
 
@@ -45,12 +58,23 @@ impl ThreadBuilder {
         self
     }
 
+    pub fn with_tool_resources(mut self, tool_resources: ToolResources) -> Self {
+        self.tool_resources = Some(tool_resources);
+        self
+    }
+
     pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
         self.metadata = Some(metadata);
         self
     }
 
+    #[cfg(feature = "blocking")]
     pub fn build(&self, networking: &Networking) -> Result<Thread, OpenApiError> {
         networking.create_thread(self)
     }
+
+    #[cfg(feature = "async")]
+    pub async fn build_async(&self, networking: &AsyncNetworking) -> Result<Thread, OpenApiError> {
+        networking.create_thread(self).await
+    }
 }
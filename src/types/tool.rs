@@ -0,0 +1,481 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use crate::networking::{AsyncChatCompletionActions, AsyncNetworking};
+#[cfg(feature = "blocking")]
+use crate::networking::{ChatCompletionActions, Networking};
+use crate::types::chat::{ChatCompletion, ChatCompletionBuilder};
+use crate::types::common::{ToolCalls, ToolChoice, Tools, Usage};
+use crate::types::error::OpenApiError;
+use crate::types::message::CompletionMessage;
+use crate::types::schema::ToolArgs;
+
+/// The number of chat completions a `ToolRunner` requests at most, unless set otherwise.
+const DEFAULT_MAX_ITERATIONS: u32 = 10;
+
+type ToolHandler = Arc<dyn Fn(&str) -> Result<String, OpenApiError> + Send + Sync>;
+
+/// `ToolRegistry` maps the names of function tools to the closures handling their calls, which
+/// receive the `arguments` generated by the model and return the content of the tool message.
+///
+/// # Examples
+/// ```ignore
+/// let registry = ToolRegistry::new()
+///     .add_tool(get_time_tool, |_arguments| Ok(Utc::now().to_rfc3339()))?
+///     .add_typed_tool(|args: GetWeatherArgs| Ok(args.call()))?;
+/// ```
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: BTreeMap<String, RegisteredTool>,
+}
+
+#[derive(Clone)]
+struct RegisteredTool {
+    definition: Tools,
+    handler: ToolHandler,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        ToolRegistry::default()
+    }
+
+    /// Registers `handler` for the function tool `definition`, replacing any tool of the same
+    /// name.
+    pub fn add_tool<F>(mut self, definition: Tools, handler: F) -> Result<Self, OpenApiError>
+    where
+        F: Fn(&str) -> Result<String, OpenApiError> + Send + Sync + 'static,
+    {
+        let name = match definition.get_function() {
+            Some(function) => function.get_name().to_string(),
+            None => {
+                return Err(OpenApiError::RestrictedValue(
+                    "Only function tools can be added to a ToolRegistry".into(),
+                ))
+            }
+        };
+        let handler = Arc::new(handler);
+        self.tools.insert(
+            name,
+            RegisteredTool {
+                definition,
+                handler,
+            },
+        );
+        Ok(self)
+    }
+
+    /// Registers the tool defined by `A`, whose calls are decoded before being passed to
+    /// `handler`, see `ToolArgs`.
+    pub fn add_typed_tool<A, F>(self, handler: F) -> Result<Self, OpenApiError>
+    where
+        A: ToolArgs,
+        F: Fn(A) -> Result<String, OpenApiError> + Send + Sync + 'static,
+    {
+        self.add_tool(A::definition()?, move |arguments| {
+            handler(A::from_arguments(arguments)?)
+        })
+    }
+
+    /// Returns the definitions of the registered tools, ordered by name.
+    pub fn get_definitions(&self) -> Vec<Tools> {
+        self.tools
+            .values()
+            .map(|tool| tool.definition.clone())
+            .collect()
+    }
+
+    /// Runs the handler of `tool_call`, failing when no tool of its name is registered.
+    pub fn call(&self, tool_call: &ToolCalls) -> Result<String, OpenApiError> {
+        self.handler(tool_call)?(tool_call.get_function().get_arguments())
+    }
+
+    fn handler(&self, tool_call: &ToolCalls) -> Result<ToolHandler, OpenApiError> {
+        let name = tool_call.get_function().get_name();
+        match self.tools.get(name) {
+            Some(tool) => Ok(tool.handler.clone()),
+            None => Err(OpenApiError::ClientError(format!(
+                "No tool named {} is registered",
+                name
+            ))),
+        }
+    }
+}
+
+/// `ToolRunner` drives the tool loop of a chat completion: while the model responds with tool
+/// calls, the calls are run through a `ToolRegistry` and their results sent back with
+/// `CompletionMessage::new_tool`. Parallel tool calls are run concurrently.
+///
+/// A failing call is reported to the model as the content of its tool message, so it can
+/// correct the call. Only the first choice is followed. A `tool_choice` forcing a tool call
+/// (`Required` or a named function) only applies to the first completion, later ones use `Auto`
+/// so the model can reply.
+///
+/// # Examples
+/// ```ignore
+/// let builder = ChatCompletionBuilder::new("gpt-4o", messages);
+/// let run = ToolRunner::new(builder, &registry).with_max_iterations(5)?.run(client.netref())?;
+/// println!("{:?} using {} tokens", run.get_content(), run.get_usage().get_total_tokens());
+/// ```
+pub struct ToolRunner<'a> {
+    builder: ChatCompletionBuilder,
+    registry: &'a ToolRegistry,
+    max_iterations: u32,
+}
+
+impl<'a> ToolRunner<'a> {
+    /// The tools of `registry` replace any set on `builder`.
+    pub fn new(builder: ChatCompletionBuilder, registry: &'a ToolRegistry) -> Self {
+        Self {
+            builder: builder.with_tools(registry.get_definitions()),
+            registry,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Caps the number of chat completions requested, by default 10. Once reached the run ends,
+    /// even if the last completion requested tool calls, see `ToolRun::is_finished`.
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Result<Self, OpenApiError> {
+        if max_iterations == 0 {
+            return Err(OpenApiError::RestrictedValue(
+                "Max Iterations must be at least 1".into(),
+            ));
+        }
+        self.max_iterations = max_iterations;
+        Ok(self)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn run(&self, networking: &Networking) -> Result<ToolRun, OpenApiError> {
+        let mut state = ToolLoop::new(self.builder.clone());
+        loop {
+            let completion = networking.create_chat_completion(&state.builder)?;
+            let tool_calls = state.receive(&completion)?;
+            if tool_calls.is_empty() || state.iterations == self.max_iterations {
+                return Ok(state.finish(completion, tool_calls.is_empty()));
+            }
+            let results = std::thread::scope(|scope| {
+                let handles: Vec<_> = tool_calls
+                    .iter()
+                    .map(|tool_call| scope.spawn(|| self.registry.call(tool_call)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect()
+            });
+            state.respond(&tool_calls, results);
+        }
+    }
+
+    /// Handlers are run with `tokio::task::spawn_blocking`, so they may block.
+    #[cfg(feature = "async")]
+    pub async fn run_async(&self, networking: &AsyncNetworking) -> Result<ToolRun, OpenApiError> {
+        let mut state = ToolLoop::new(self.builder.clone());
+        loop {
+            let completion = networking.create_chat_completion(&state.builder).await?;
+            let tool_calls = state.receive(&completion)?;
+            if tool_calls.is_empty() || state.iterations == self.max_iterations {
+                return Ok(state.finish(completion, tool_calls.is_empty()));
+            }
+            let handles: Vec<_> = tool_calls
+                .iter()
+                .map(|tool_call| {
+                    let handler = self.registry.handler(tool_call);
+                    let arguments = tool_call.get_function().get_arguments().to_string();
+                    tokio::task::spawn_blocking(move || handler?(&arguments))
+                })
+                .collect();
+            let mut results = Vec::with_capacity(handles.len());
+            for handle in handles {
+                results.push(match handle.await {
+                    Ok(result) => result,
+                    Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                    Err(err) => Err(OpenApiError::ClientError(err.to_string())),
+                });
+            }
+            state.respond(&tool_calls, results);
+        }
+    }
+}
+
+/// The state of a run, shared by `run` and `run_async`.
+struct ToolLoop {
+    builder: ChatCompletionBuilder,
+    usage: Usage,
+    iterations: u32,
+}
+
+impl ToolLoop {
+    fn new(builder: ChatCompletionBuilder) -> Self {
+        Self {
+            builder,
+            usage: Usage::default(),
+            iterations: 0,
+        }
+    }
+
+    /// Appends the reply of `completion` to the transcript, returning the tool calls it requests.
+    fn receive(&mut self, completion: &ChatCompletion) -> Result<Vec<ToolCalls>, OpenApiError> {
+        self.iterations += 1;
+        if let Some(usage) = completion.get_usage() {
+            self.usage += usage;
+        }
+        let message = completion
+            .get_choices()
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.get_message())
+            .ok_or_else(|| OpenApiError::ClientError("Chat completion has no message".into()))?;
+        let tool_calls = message.get_tool_calls().to_vec();
+        let mut builder = std::mem::take(&mut self.builder)
+            .add_message(CompletionMessage::AssistantMessage(message));
+        if matches!(
+            builder.get_tool_choice(),
+            Some(ToolChoice::Required | ToolChoice::Function(_))
+        ) {
+            builder = builder.with_tool_choice(ToolChoice::Auto);
+        }
+        self.builder = builder;
+        Ok(tool_calls)
+    }
+
+    fn respond(&mut self, tool_calls: &[ToolCalls], results: Vec<Result<String, OpenApiError>>) {
+        for (tool_call, result) in tool_calls.iter().zip(results) {
+            let content = result.unwrap_or_else(|err| err.to_string());
+            let message =
+                CompletionMessage::new_tool(content, Some(tool_call.get_id().to_string()));
+            self.builder = std::mem::take(&mut self.builder).add_message(message);
+        }
+    }
+
+    fn finish(self, completion: ChatCompletion, finished: bool) -> ToolRun {
+        ToolRun {
+            messages: self.builder.get_messages().to_vec(),
+            completion,
+            usage: self.usage,
+            iterations: self.iterations,
+            finished,
+        }
+    }
+}
+
+/// `ToolRun` is the outcome of a `ToolRunner`.
+///
+/// # Fields
+///
+/// * `messages: Vec<CompletionMessage>` - The transcript, from the initial messages to the last reply.
+/// * `completion: ChatCompletion` - The last chat completion received.
+/// * `usage: Usage` - The usage summed over every chat completion.
+/// * `iterations: u32` - The number of chat completions requested.
+/// * `finished: bool` - False when the run was cut off by the max iterations with tool calls pending.
+///
+#[derive(Clone, Debug)]
+pub struct ToolRun {
+    messages: Vec<CompletionMessage>,
+    completion: ChatCompletion,
+    usage: Usage,
+    iterations: u32,
+    finished: bool,
+}
+
+impl ToolRun {
+    pub fn get_messages(&self) -> &[CompletionMessage] {
+        &self.messages
+    }
+
+    pub fn get_completion(&self) -> &ChatCompletion {
+        &self.completion
+    }
+
+    /// Returns the content of the last reply.
+    pub fn get_content(&self) -> Option<String> {
+        self.completion.get_message_content()
+    }
+
+    pub fn get_usage(&self) -> &Usage {
+        &self.usage
+    }
+
+    pub fn get_iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networking::transport::MockTransport;
+    use crate::networking::NetworkingConfig;
+    use crate::types::common::ToolFunction;
+    use crate::types::schema::JsonSchema;
+    use reqwest::{Method, StatusCode};
+    use serde_json::{json, Value};
+
+    fn completion(message: Value, total_tokens: u32) -> Value {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "gpt-4",
+            "system_fingerprint": null,
+            "choices": [{"index": 0, "message": message, "logprobs": null, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": total_tokens - 1, "completion_tokens": 1, "total_tokens": total_tokens},
+        })
+    }
+
+    fn tool_call(id: &str, name: &str, arguments: &str) -> Value {
+        json!({"id": id, "type": "function", "function": {"name": name, "arguments": arguments}})
+    }
+
+    fn registry() -> ToolRegistry {
+        let parameters = JsonSchema::object().with_property("city", JsonSchema::string());
+        let weather =
+            Tools::function(ToolFunction::new("get_weather").with_parameters(parameters)).unwrap();
+        ToolRegistry::new()
+            .add_tool(weather, |arguments| {
+                let arguments: Value = serde_json::from_str(arguments)?;
+                Ok(format!("Sunny in {}", arguments["city"].as_str().unwrap()))
+            })
+            .unwrap()
+    }
+
+    /// A completion calling two tools, one of them unknown, followed by the final reply.
+    fn tool_loop_mock() -> MockTransport {
+        let calls = json!({"role": "assistant", "content": null, "tool_calls": [
+            tool_call("call_1", "get_weather", r#"{"city": "Paris"}"#),
+            tool_call("call_2", "get_time", "{}"),
+        ]});
+        let reply = json!({"role": "assistant", "content": "It is sunny in Paris"});
+        MockTransport::new()
+            .with_json(
+                Method::POST,
+                "chat/completions",
+                StatusCode::OK,
+                &completion(calls, 10),
+            )
+            .unwrap()
+            .with_json(
+                Method::POST,
+                "chat/completions",
+                StatusCode::OK,
+                &completion(reply, 20),
+            )
+            .unwrap()
+    }
+
+    fn assert_tool_loop_run(run: &ToolRun, mock: &MockTransport) {
+        assert!(run.is_finished());
+        assert_eq!(run.get_iterations(), 2);
+        assert_eq!(run.get_content().unwrap(), "It is sunny in Paris");
+        assert_eq!(run.get_usage().get_total_tokens(), 30);
+        assert_eq!(run.get_messages().len(), 5);
+
+        let request = mock.requests()[1].json_body().unwrap().clone();
+        assert_eq!(request["tools"][0]["function"]["name"], "get_weather");
+        assert_eq!(
+            request["messages"][2],
+            json!({"role": "tool", "content": "Sunny in Paris", "tool_call_id": "call_1"})
+        );
+        assert_eq!(
+            request["messages"][3]["content"],
+            "ClientError: No tool named get_time is registered"
+        );
+    }
+
+    fn weather_question() -> ChatCompletionBuilder {
+        ChatCompletionBuilder::new(
+            "gpt-4",
+            vec![CompletionMessage::new_user(
+                "Weather in Paris?".into(),
+                None,
+            )],
+        )
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_tool_loop() {
+        let mock = tool_loop_mock();
+        let networking = Networking::new(NetworkingConfig::default()).with_transport(mock.clone());
+        let registry = registry();
+        let run = ToolRunner::new(weather_question(), &registry)
+            .run(&networking)
+            .unwrap();
+        assert_tool_loop_run(&run, &mock);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_tool_loop_async() {
+        let mock = tool_loop_mock();
+        let networking =
+            AsyncNetworking::new(NetworkingConfig::default()).with_transport(mock.clone());
+        let registry = registry();
+        let run = ToolRunner::new(weather_question(), &registry)
+            .run_async(&networking)
+            .await
+            .unwrap();
+        assert_tool_loop_run(&run, &mock);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_forced_tool_choice_only_applies_once() {
+        let mock = tool_loop_mock();
+        let networking = Networking::new(NetworkingConfig::default()).with_transport(mock.clone());
+        let registry = registry();
+        let builder = weather_question().with_tool_choice(ToolChoice::function("get_weather"));
+        let run = ToolRunner::new(builder, &registry)
+            .run(&networking)
+            .unwrap();
+
+        assert!(run.is_finished());
+        let requests = mock.requests();
+        assert_eq!(
+            requests[0].json_body().unwrap()["tool_choice"]["function"]["name"],
+            "get_weather"
+        );
+        assert_eq!(requests[1].json_body().unwrap()["tool_choice"], "auto");
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_tool_loop_capped() {
+        let calls = json!({"role": "assistant", "content": null, "tool_calls": [
+            tool_call("call_1", "get_weather", r#"{"city": "Paris"}"#),
+        ]});
+        let mock = MockTransport::new()
+            .with_json(
+                Method::POST,
+                "chat/completions",
+                StatusCode::OK,
+                &completion(calls, 10),
+            )
+            .unwrap();
+        let networking = Networking::new(NetworkingConfig::default()).with_transport(mock.clone());
+        let builder = ChatCompletionBuilder::new("gpt-4", Vec::new());
+        let registry = registry();
+        let run = ToolRunner::new(builder, &registry)
+            .with_max_iterations(3)
+            .unwrap()
+            .run(&networking)
+            .unwrap();
+
+        assert!(!run.is_finished());
+        assert_eq!(run.get_iterations(), 3);
+        assert_eq!(mock.requests().len(), 3);
+        assert!(ToolRunner::new(ChatCompletionBuilder::default(), &registry)
+            .with_max_iterations(0)
+            .is_err());
+    }
+}